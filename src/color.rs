@@ -0,0 +1,94 @@
+use std::env;
+
+use crate::subnet::{Subnet, Verbosity};
+
+/// ANSI escape code that resets the style
+const RESET: &str = "\x1b[0m";
+
+/// Colors and styles of the terminal output, see [`paint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Bold,
+    Cyan,
+    Green,
+    Yellow,
+}
+
+impl Color {
+    /// Returns the ANSI escape code of the color
+    fn code(self) -> &'static str {
+        match self {
+            Color::Bold => "\x1b[1m",
+            Color::Cyan => "\x1b[1;36m",
+            Color::Green => "\x1b[32m",
+            Color::Yellow => "\x1b[33m",
+        }
+    }
+}
+
+/**
+ * Returns true if the output to a stream should be colored <br>
+ * The colors are disabled if the stream isn't a terminal or the `NO_COLOR` environment variable is set
+ * (and not empty), see <https://no-color.org>
+ */
+pub fn colors_enabled(is_terminal: bool) -> bool {
+    is_terminal && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// Wraps the text in the ANSI escape codes of the color
+pub fn paint(text: &str, color: Color) -> String {
+    format!("{}{}{}", color.code(), text, RESET)
+}
+
+/**
+ * Returns the subnet information of the [`Display`](std::fmt::Display) implementation with colors <br>
+ * The field labels are bold, the network address is green and the wasted hosts are yellow if there are any <br>
+ * The amount of detail is the one of the verbosity, see [`Subnet::format_with`]
+ */
+pub fn colorize_subnet(subnet: &Subnet, verbosity: Verbosity) -> String {
+    let text = subnet.format_with(verbosity);
+
+    text.lines()
+        .map(|line| match line.split_once(": ") {
+            Some((label, value)) => {
+                let value = match label.trim_start_matches(['\t', ' ', '-']) {
+                    "Network" => paint(value, Color::Green),
+                    "Wasted Hosts" if subnet.wasted_hosts() > 0 => paint(value, Color::Yellow),
+                    _ => value.to_string(),
+                };
+                format!("{}: {}", paint(label, Color::Bold), value)
+            }
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_color_suppresses_colors() {
+        env::set_var("NO_COLOR", "1");
+        assert!(!colors_enabled(true));
+        env::remove_var("NO_COLOR");
+    }
+
+    #[test]
+    fn colors_are_disabled_without_terminal() {
+        assert!(!colors_enabled(false));
+    }
+
+    #[test]
+    fn colorized_subnet_highlights_fields() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 100).unwrap();
+        subnet.calculate().unwrap();
+
+        let text = colorize_subnet(&subnet, Verbosity::Normal);
+        assert!(text.contains(&paint("192.168.1.0", Color::Green)));
+        assert!(text.contains(&paint("28", Color::Yellow)));
+        assert!(text.contains(&paint("\t - Mask", Color::Bold)));
+        assert_eq!(text.lines().count(), format!("{}", subnet).lines().count());
+    }
+}
@@ -1,11 +1,66 @@
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{self, BufRead};
-use std::num::ParseIntError;
+use std::io::{self, BufRead, Read};
 use std::path::Path;
 
+use crate::subnet::{HostRequirement, NetworkSpec, Subnet, SubnetError};
+
+/// Prefix of the IPv4-mapped IPv6 addresses (RFC 4291), e.g. `::ffff:192.168.1.0`
+const IPV4_MAPPED_PREFIX: &str = "::ffff:";
+
+/// Default maximum number of subnets of an imported file, see [`import_csv_with_max`]
+pub const DEFAULT_MAX_SUBNETS: usize = 65536;
+
+/// Network and host requirements of an imported file (or of the standard input)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkInput {
+    /// IP address of the network (e.g. `192.168.1.0`)
+    pub ip: String,
+    /// Prefix length of the network
+    pub cidr: u32,
+    /// Number of hosts, prefix length or growth of each subnet
+    pub host_requirements: Vec<HostRequirement>,
+    /// Names of the subnets, empty if the subnets aren't named
+    pub names: Vec<String>,
+    /// Tags of the subnets (see [`Subnet::extra`]), empty if no subnet has any, only a JSON import has tags
+    pub extra: Vec<BTreeMap<String, String>>,
+}
+
+impl NetworkInput {
+    /**
+     * Returns the indexes of the requests repeating an earlier request with the same name and host requirement
+     * (e.g. a department listed twice) <br>
+     * Unnamed requests are never duplicates, since two subnets of the same size are usually intended
+     */
+    pub fn duplicates(&self) -> Vec<usize> {
+        (0..self.names.len().min(self.host_requirements.len()))
+            .filter(|&i| {
+                (0..i).any(|j| {
+                    self.names[j] == self.names[i]
+                        && self.host_requirements[j] == self.host_requirements[i]
+                })
+            })
+            .collect()
+    }
+
+    /// Removes the duplicate requests, see [`NetworkInput::duplicates`], and returns how many were collapsed
+    pub fn dedupe(&mut self) -> usize {
+        let duplicates = self.duplicates();
+        for &i in duplicates.iter().rev() {
+            self.names.remove(i);
+            self.host_requirements.remove(i);
+            // The tags are empty if no subnet has any, so they're removed only if there are some
+            if i < self.extra.len() {
+                self.extra.remove(i);
+            }
+        }
+        duplicates.len()
+    }
+}
+
 /**
  * Imports a CSV file <br>
- * Example:
+ * The first line contains the network, followed by one number of hosts (or prefix length like `/28`) per line:
  * ```csv
  * ip,cidr
  * number_of_hosts1
@@ -13,31 +68,491 @@ use std::path::Path;
  * ...
  * number_of_hostsN
  * ```
+ * Alternatively, the network can be followed by a two-column CSV with a header row,
+ * where each row contains the name of the subnet and its number of hosts:
+ * ```csv
+ * ip,cidr
+ * subnet_name,host_count
+ * name1,number_of_hosts1
+ * ...
+ * nameN,number_of_hostsN
+ * ```
+ * Comments starting with `#`, quotes around the lines and blank lines are ignored, see [`clean_line`] <br>
+ * A network without a CIDR (e.g. `10.0.0.0`) gets the classful default prefix of its class, see [`NetworkSpec::parse_classful`]
+ */
+pub fn import_csv(file_path: &str) -> Result<NetworkInput, SubnetError> {
+    import_csv_with_max(file_path, DEFAULT_MAX_SUBNETS)
+}
+
+/**
+ * Same as [`import_csv`], with a maximum number of subnets instead of [`DEFAULT_MAX_SUBNETS`] <br>
+ * Returns [`SubnetError::TooManyImportedSubnets`] if the file has more subnets, without reading the rest of it,
+ * so a malformed or malicious file with millions of lines can't exhaust the memory
  */
-pub fn import_csv(file_path: &str) -> Result<(String, u32, Vec<u32>), Box<dyn std::error::Error>> {
+pub fn import_csv_with_max(
+    file_path: &str,
+    max_subnets: usize,
+) -> Result<NetworkInput, SubnetError> {
     let file = File::open(Path::new(file_path))?;
-    let reader = io::BufReader::new(file);
-    let mut lines = reader.lines();
+    read_csv_with_max(io::BufReader::new(file), max_subnets)
+}
+
+/// Reads a CSV network specification from any reader (e.g. the standard input), see [`import_csv`] for the format
+pub fn read_csv<R: BufRead>(reader: R) -> Result<NetworkInput, SubnetError> {
+    read_csv_with_max(reader, DEFAULT_MAX_SUBNETS)
+}
+
+/// Same as [`read_csv`], with a maximum number of subnets, see [`import_csv_with_max`]
+pub fn read_csv_with_max<R: BufRead>(
+    reader: R,
+    max_subnets: usize,
+) -> Result<NetworkInput, SubnetError> {
+    let too_many = SubnetError::TooManyImportedSubnets { max: max_subnets };
+
+    // The network, the header of the named subnets and the subnets, the lines after the limit aren't read
+    let mut lines = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = clean_line(&line);
+        if line.is_empty() {
+            continue;
+        }
+        if lines.len() > max_subnets + 1 {
+            return Err(too_many);
+        }
+        lines.push(line.to_string());
+    }
+    let mut lines = lines.iter().map(String::as_str);
 
     // Read and parse the first line
-    let first_line = lines.next().ok_or("File is empty")??;
+    let first_line = lines
+        .next()
+        .ok_or_else(|| SubnetError::InvalidImport("File is empty".to_string()))?;
+
+    // An IPv4-mapped IPv6 address (e.g. ::ffff:192.168.1.0) is read as the IPv4 address
+    let first_line = first_line
+        .get(..IPV4_MAPPED_PREFIX.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(IPV4_MAPPED_PREFIX))
+        .map_or(first_line, |prefix| &first_line[prefix.len()..]);
 
-    let parts: Vec<&str> = first_line.split(&[',', '/'][..]).collect();
-    let (ip, cidr) = match parts.as_slice() {
-        [ip, cidr] => (ip.trim().to_string(), cidr.trim().parse()?),
-        _ => return Err(format!("Invalid first line format: {}", first_line).into()),
+    // The network can be separated from the CIDR with a comma or a slash, or have no CIDR for the classful default
+    let spec = NetworkSpec::parse_classful(&first_line.replacen(',', "/", 1))?;
+
+    let lines: Vec<&str> = lines.collect();
+
+    let (host_requirements, names) = match lines.first() {
+        Some(header) if header.contains(',') => parse_named_hosts(&lines.join("\n"))?,
+        _ => {
+            let num_hosts_array = lines
+                .iter()
+                .map(|line| line.parse())
+                .collect::<Result<_, _>>()?;
+            (num_hosts_array, Vec::new())
+        }
     };
 
-    let num_hosts_array: Vec<u32> = lines
-        .map(|line| {
-            let line = line?;
-            line.trim().parse().map_err(|e: ParseIntError| e.into())
-        })
-        .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+    if host_requirements.is_empty() {
+        return Err(SubnetError::InvalidImport(
+            "No host numbers found in the file".to_string(),
+        ));
+    }
+    if host_requirements.len() > max_subnets {
+        return Err(too_many);
+    }
 
-    if num_hosts_array.is_empty() {
-        return Err("No host numbers found in the file".into());
+    Ok(NetworkInput {
+        ip: spec.network.to_string(),
+        cidr: spec.cidr,
+        host_requirements,
+        names,
+        extra: Vec::new(),
+    })
+}
+
+/**
+ * Helper function to strip the `#` comment, the surrounding whitespace and the surrounding quotes of a line
+ * (e.g. `"192.168.1.0/24" # core` becomes `192.168.1.0/24`), as written by some spreadsheets
+ */
+fn clean_line(line: &str) -> &str {
+    let line = line
+        .split_once('#')
+        .map_or(line, |(content, _)| content)
+        .trim();
+    ['"', '\'']
+        .iter()
+        .find_map(|quote| line.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(line)
+        .trim()
+}
+
+/**
+ * Imports the subnets of a CSV file written by [`SaveToFile::save_csv`](crate::save_file::SaveToFile::save_csv) <br>
+ * Unlike [`import_csv`] it reads calculated subnets, one per row with a header row
+ */
+pub fn import_subnets_csv(file_path: &str) -> Result<Vec<Subnet>, csv::Error> {
+    let file = File::open(Path::new(file_path))?;
+    read_subnets_csv(file)
+}
+
+/// Reads the subnets of a CSV written by [`SaveToFile::save_csv`](crate::save_file::SaveToFile::save_csv) from any reader
+pub fn read_subnets_csv<R: Read>(reader: R) -> Result<Vec<Subnet>, csv::Error> {
+    csv::Reader::from_reader(reader).deserialize().collect()
+}
+
+/// Helper function to parse a two-column CSV (with a header row) of subnet names and numbers of hosts
+fn parse_named_hosts(data: &str) -> Result<(Vec<HostRequirement>, Vec<String>), SubnetError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data.as_bytes());
+
+    let mut num_hosts_array = Vec::new();
+    let mut names = Vec::new();
+
+    for record in reader.records() {
+        let record = record?;
+        if record.len() != 2 {
+            return Err(SubnetError::InvalidImport(format!(
+                "Invalid row: {:?}",
+                record
+            )));
+        }
+        names.push(record[0].to_string());
+        num_hosts_array.push(record[1].parse()?);
     }
 
-    Ok((ip, cidr, num_hosts_array))
+    Ok((num_hosts_array, names))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_file::SaveToFile;
+    use crate::subnets_calculator::SubnetCalculator;
+
+    /// Helper function to write the CSV data to a temporary file and import it
+    fn import_str(file_name: &str, data: &str) -> Result<NetworkInput, SubnetError> {
+        let path = std::env::temp_dir().join(file_name);
+        std::fs::write(&path, data)?;
+        let result = import_csv(path.to_str().unwrap());
+        std::fs::remove_file(&path)?;
+        result
+    }
+
+    /// Helper function to write the CSV data to a temporary file and import it with a maximum number of subnets
+    fn import_str_with_max(
+        file_name: &str,
+        data: &str,
+        max_subnets: usize,
+    ) -> Result<NetworkInput, SubnetError> {
+        let path = std::env::temp_dir().join(file_name);
+        std::fs::write(&path, data)?;
+        let result = import_csv_with_max(path.to_str().unwrap(), max_subnets);
+        std::fs::remove_file(&path)?;
+        result
+    }
+
+    #[test]
+    fn import_host_per_line() {
+        let input = import_str(
+            "subnetting_import_host_per_line.csv",
+            "192.168.1.0/24\n50\n20\n",
+        )
+        .unwrap();
+        assert_eq!(
+            input,
+            NetworkInput {
+                ip: "192.168.1.0".to_string(),
+                cidr: 24,
+                host_requirements: vec![HostRequirement::Hosts(50), HostRequirement::Hosts(20)],
+                names: Vec::new(),
+                extra: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn import_named_two_columns() {
+        let input = import_str(
+            "subnetting_import_named_two_columns.csv",
+            "10.0.0.0,24\nsubnet_name,host_count\nSales,50\nDMZ, 10\n",
+        )
+        .unwrap();
+        assert_eq!(input.ip, "10.0.0.0");
+        assert_eq!(input.cidr, 24);
+        assert_eq!(
+            input.host_requirements,
+            vec![HostRequirement::Hosts(50), HostRequirement::Hosts(10)]
+        );
+        assert_eq!(input.names, vec!["Sales", "DMZ"]);
+    }
+
+    #[test]
+    fn named_import_reaches_markdown_table() {
+        let input = import_str(
+            "subnetting_named_import_reaches_markdown_table.csv",
+            "10.0.0.0/24\nsubnet_name,host_count\nDMZ,10\nSales,50\n",
+        )
+        .unwrap();
+        let mut calculator = SubnetCalculator::from_requirements(&input.host_requirements);
+        calculator.names = input.names;
+        calculator.calculate(&input.ip, input.cidr).unwrap();
+
+        let mut markdown = Vec::new();
+        crate::save_file::write_md(&calculator.subnets, &mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+
+        assert!(markdown.contains("| **Name** | **Network** |"));
+        assert!(markdown.contains("| Sales | 10.0.0.0 |"));
+        assert!(markdown.contains("| DMZ | 10.0.0.64 |"));
+    }
+
+    #[test]
+    fn duplicate_named_requests_are_collapsed() {
+        let mut input = read_csv(io::Cursor::new(
+            b"10.0.0.0/24\nsubnet_name,host_count\nSales,50\nDMZ,10\nSales,50\nSales,20\n",
+        ))
+        .unwrap();
+        assert_eq!(input.duplicates(), vec![2]);
+        assert_eq!(input.dedupe(), 1);
+        assert_eq!(input.names, vec!["Sales", "DMZ", "Sales"]);
+        assert_eq!(
+            input.host_requirements,
+            vec![
+                HostRequirement::Hosts(50),
+                HostRequirement::Hosts(10),
+                HostRequirement::Hosts(20)
+            ]
+        );
+
+        // The same number of hosts without names isn't a duplicate
+        let mut input = read_csv(io::Cursor::new(b"10.0.0.0/24\n50\n50\n")).unwrap();
+        assert!(input.duplicates().is_empty());
+        assert_eq!(input.dedupe(), 0);
+    }
+
+    #[test]
+    fn dedupe_keeps_the_tags_aligned() {
+        let tags = |vlan: &str| BTreeMap::from([("vlan".to_string(), vlan.to_string())]);
+        let mut input = NetworkInput {
+            ip: "10.0.0.0".to_string(),
+            cidr: 24,
+            host_requirements: vec![HostRequirement::Hosts(50); 3],
+            names: vec!["A".to_string(), "A".to_string(), "B".to_string()],
+            extra: vec![tags("1"), tags("1"), tags("2")],
+        };
+        assert_eq!(input.dedupe(), 1);
+        assert_eq!(input.names, vec!["A", "B"]);
+        assert_eq!(input.extra, vec![tags("1"), tags("2")]);
+    }
+
+    #[test]
+    fn import_prefixes_mixed_with_hosts() {
+        let input = import_str(
+            "subnetting_import_prefixes_mixed_with_hosts.csv",
+            "10.0.0.0/24\n/28\n50\n",
+        )
+        .unwrap();
+        assert_eq!(
+            input.host_requirements,
+            vec![HostRequirement::Prefix(28), HostRequirement::Hosts(50)]
+        );
+    }
+
+    #[test]
+    fn import_hosts_with_growth() {
+        let input = import_str(
+            "subnetting_import_hosts_with_growth.csv",
+            "10.0.0.0/24\n50+30%\n10*2\n",
+        )
+        .unwrap();
+        assert_eq!(
+            input.host_requirements,
+            vec![
+                HostRequirement::Growth {
+                    hosts: 50,
+                    grown: 65
+                },
+                HostRequirement::Growth {
+                    hosts: 10,
+                    grown: 20
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn import_commented_network() {
+        let input = import_str(
+            "subnetting_import_commented_network.csv",
+            "192.168.1.0/24 # core\n50 # servers\n",
+        )
+        .unwrap();
+        assert_eq!(input.ip, "192.168.1.0");
+        assert_eq!(input.cidr, 24);
+        assert_eq!(input.host_requirements, vec![HostRequirement::Hosts(50)]);
+    }
+
+    #[test]
+    fn import_network_with_classful_default() {
+        let input = read_csv(io::Cursor::new(b"172.16.0.0\n50\n")).unwrap();
+        assert_eq!(input.ip, "172.16.0.0");
+        assert_eq!(input.cidr, 16);
+    }
+
+    #[test]
+    fn import_ipv4_mapped_network() {
+        let input = read_csv(io::Cursor::new(b"::FFFF:10.0.0.0/24\n50\n")).unwrap();
+        assert_eq!(input.ip, "10.0.0.0");
+        assert_eq!(input.cidr, 24);
+    }
+
+    #[test]
+    fn import_quoted_values() {
+        let input = import_str(
+            "subnetting_import_quoted_values.csv",
+            "\"10.0.0.0/24\"\n\"50\"\n'/28'\n",
+        )
+        .unwrap();
+        assert_eq!(input.ip, "10.0.0.0");
+        assert_eq!(
+            input.host_requirements,
+            vec![HostRequirement::Hosts(50), HostRequirement::Prefix(28)]
+        );
+    }
+
+    #[test]
+    fn import_blank_lines_between_hosts() {
+        let input = import_str(
+            "subnetting_import_blank_lines_between_hosts.csv",
+            "\n10.0.0.0/24\n\n50\n   \n# spare\n20\n\n",
+        )
+        .unwrap();
+        assert_eq!(
+            input.host_requirements,
+            vec![HostRequirement::Hosts(50), HostRequirement::Hosts(20)]
+        );
+
+        assert!(import_str(
+            "subnetting_import_blank_lines_invalid_hosts.csv",
+            "10.0.0.0/24\n\n5O # typo\n",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn imported_subnets_overflowing_parent_name_first_overflow() {
+        let input = read_csv(io::Cursor::new(b"10.0.0.0/28\n2\n2\n2\n6\n")).unwrap();
+        let mut calculator = SubnetCalculator::from_requirements(&input.host_requirements);
+
+        // The /29 of line 4 and the /30s of lines 1 and 2 fill the /28, the /30 of line 3 overflows it
+        let error = calculator.calculate(&input.ip, input.cidr).unwrap_err();
+        assert!(matches!(
+            error,
+            SubnetError::InsufficientSpace {
+                requested: 20,
+                available: 16,
+                first_overflow: Some(2)
+            }
+        ));
+        assert!(error
+            .to_string()
+            .ends_with("subnet #3 of the input is the first that doesn't fit"));
+    }
+
+    #[test]
+    fn malformed_csv_row_is_csv_error() {
+        let error = read_csv(io::Cursor::new(
+            b"10.0.0.0/24\nsubnet_name,host_count\nSales,50,extra\n",
+        ))
+        .unwrap_err();
+        assert!(matches!(error, SubnetError::CsvError(_)));
+        assert_eq!(error.exit_code(), 45);
+
+        let error = read_csv(io::Cursor::new(b"# nothing but a comment\n")).unwrap_err();
+        assert!(matches!(error, SubnetError::InvalidImport(_)));
+    }
+
+    #[test]
+    fn import_over_max_subnets_is_an_error() {
+        let error = import_str_with_max(
+            "subnetting_import_over_max_subnets.csv",
+            "10.0.0.0/16\n50\n20\n10\n2\n",
+            3,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            SubnetError::TooManyImportedSubnets { max: 3 }
+        ));
+
+        let input = import_str_with_max(
+            "subnetting_import_at_max_subnets.csv",
+            "10.0.0.0/16\nsubnet_name,host_count\nA,50\nB,20\nC,10\n",
+            3,
+        )
+        .unwrap();
+        assert_eq!(input.host_requirements.len(), 3);
+    }
+
+    #[test]
+    fn endless_input_stops_at_max_subnets() {
+        /// Reader of a network followed by an endless list of hosts
+        struct Endless(bool);
+
+        impl Read for Endless {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if !std::mem::replace(&mut self.0, true) {
+                    buf[..11].copy_from_slice(b"10.0.0.0/8\n");
+                    return Ok(11);
+                }
+                buf[..2].copy_from_slice(b"2\n");
+                Ok(2)
+            }
+        }
+
+        let error = read_csv_with_max(io::BufReader::new(Endless(false)), 1000).unwrap_err();
+        assert!(matches!(
+            error,
+            SubnetError::TooManyImportedSubnets { max: 1000 }
+        ));
+    }
+
+    #[test]
+    fn read_csv_from_cursor() {
+        let cursor = io::Cursor::new(b"172.16.0.0,16\n1000\n/24\n");
+        let input = read_csv(cursor).unwrap();
+        assert_eq!(input.ip, "172.16.0.0");
+        assert_eq!(input.cidr, 16);
+        assert_eq!(
+            input.host_requirements,
+            vec![HostRequirement::Hosts(1000), HostRequirement::Prefix(24)]
+        );
+        assert!(input.names.is_empty());
+    }
+
+    #[test]
+    fn saved_subnets_csv_round_trip() {
+        let mut calculator = SubnetCalculator::new(vec![100, 50, 2]);
+        calculator.calculate("255.255.255.0", 24).unwrap();
+
+        let path = std::env::temp_dir().join("subnetting_saved_subnets_csv_round_trip.csv");
+        let save = SaveToFile::new(path.to_str().unwrap(), calculator.subnets.clone());
+        save.save_csv().unwrap();
+
+        let subnets = import_subnets_csv(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            format!("{:?}", subnets),
+            format!("{:?}", calculator.subnets)
+        );
+    }
+
+    #[test]
+    fn import_named_invalid_hosts() {
+        assert!(import_str(
+            "subnetting_import_named_invalid_hosts.csv",
+            "10.0.0.0,24\nsubnet_name,host_count\nSales,many\n",
+        )
+        .is_err());
+    }
 }
@@ -0,0 +1,105 @@
+use std::io::{self, BufRead};
+
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+/**
+ * Source of the answers typed at the interactive prompts, see [`read_answer`] <br>
+ * Any buffered reader is a source, e.g. the locked standard input or an `io::Cursor` of scripted answers,
+ * and so is a [`LineEditor`] on a terminal
+ */
+pub trait LineSource {
+    /// Reads the next line with its line ending, `None` if the input has ended
+    fn next_line(&mut self) -> io::Result<Option<String>>;
+}
+
+impl<R: BufRead> LineSource for R {
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+}
+
+/**
+ * Line editor with history for the answers typed on a terminal, see [`LineSource`] <br>
+ * The prompt is shown by the editor itself, so it's set with [`LineEditor::prompt`] instead of printed
+ */
+pub struct LineEditor {
+    editor: DefaultEditor,
+    prompt: String,
+}
+
+impl LineEditor {
+    /// Creates a line editor with an empty history
+    pub fn new() -> io::Result<LineEditor> {
+        let editor = DefaultEditor::new().map_err(readline_to_io)?;
+        Ok(LineEditor {
+            editor,
+            prompt: String::new(),
+        })
+    }
+
+    /// Appends the text to the prompt shown before the next line, it's cleared once the line is read
+    pub fn prompt(&mut self, text: &str) {
+        self.prompt.push_str(text);
+    }
+}
+
+impl LineSource for LineEditor {
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        let prompt = std::mem::take(&mut self.prompt);
+        match self.editor.readline(&prompt) {
+            Ok(line) => {
+                // The previous answers can be recalled with the arrow keys
+                self.editor
+                    .add_history_entry(line.as_str())
+                    .map_err(readline_to_io)?;
+                Ok(Some(line))
+            }
+            Err(ReadlineError::Eof) => Ok(None),
+            Err(error) => Err(readline_to_io(error)),
+        }
+    }
+}
+
+/// Helper function to convert an error of the line editor, Ctrl-C being [`io::ErrorKind::Interrupted`]
+fn readline_to_io(error: ReadlineError) -> io::Error {
+    match error {
+        ReadlineError::Io(error) => error,
+        ReadlineError::Interrupted => io::Error::new(io::ErrorKind::Interrupted, "Interrupted"),
+        error => io::Error::other(error),
+    }
+}
+
+/**
+ * Reads the next answer of the source without the surrounding whitespace and the line ending <br>
+ * It returns an error if the input has ended (EOF)
+ */
+pub fn read_answer<S: LineSource + ?Sized>(source: &mut S) -> io::Result<String> {
+    match source.next_line()? {
+        Some(line) => Ok(line.trim().to_string()),
+        None => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Unexpected end of input",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_answers_are_trimmed_until_eof() {
+        let mut source = io::Cursor::new("192.168.1.0/24\r\n  50 \n\n");
+        assert_eq!(read_answer(&mut source).unwrap(), "192.168.1.0/24");
+        assert_eq!(read_answer(&mut source).unwrap(), "50");
+        assert_eq!(read_answer(&mut source).unwrap(), "");
+        assert_eq!(
+            read_answer(&mut source).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::csv_import::{NetworkInput, DEFAULT_MAX_SUBNETS};
+use crate::subnet::{HostRequirement, NetworkSpec, Subnet, SubnetError};
+
+/// Network specification of a JSON file, see [`import_json`]
+#[derive(Deserialize)]
+struct JsonSpec {
+    network: String,
+    hosts: Vec<JsonHosts>,
+}
+
+/// Number of hosts of a subnet in a JSON file, optionally with a name and tags
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonHosts {
+    Count(u32),
+    Named {
+        name: String,
+        hosts: u32,
+        #[serde(default)]
+        extra: BTreeMap<String, String>,
+    },
+}
+
+/**
+ * Imports a JSON file <br>
+ * Example:
+ * ```json
+ * { "network": "10.0.0.0/24", "hosts": [50, 20, 10] }
+ * ```
+ * The subnets can be named with objects instead of numbers, e.g. `{ "name": "Sales", "hosts": 50 }` <br>
+ * The objects can also have tags, passed through to the JSON and YAML exports (see [`Subnet::extra`]),
+ * e.g. `{ "name": "Sales", "hosts": 50, "extra": { "vlan": "10", "site": "HQ" } }` <br>
+ * The returned names are empty if no subnet is named, the unnamed subnets have an empty name otherwise
+ */
+pub fn import_json(file_path: &str) -> Result<NetworkInput, SubnetError> {
+    import_json_with_max(file_path, DEFAULT_MAX_SUBNETS)
+}
+
+/**
+ * Same as [`import_json`], with a maximum number of subnets instead of [`DEFAULT_MAX_SUBNETS`] <br>
+ * Returns [`SubnetError::TooManyImportedSubnets`] if the file has more subnets
+ */
+pub fn import_json_with_max(
+    file_path: &str,
+    max_subnets: usize,
+) -> Result<NetworkInput, SubnetError> {
+    let file = File::open(Path::new(file_path))?;
+    read_json_with_max(io::BufReader::new(file), max_subnets)
+}
+
+/// Reads a JSON network specification from any reader (e.g. the standard input), see [`import_json`] for the format
+pub fn read_json<R: Read>(reader: R) -> Result<NetworkInput, SubnetError> {
+    read_json_with_max(reader, DEFAULT_MAX_SUBNETS)
+}
+
+/// Same as [`read_json`], with a maximum number of subnets, see [`import_json_with_max`]
+pub fn read_json_with_max<R: Read>(
+    reader: R,
+    max_subnets: usize,
+) -> Result<NetworkInput, SubnetError> {
+    let spec: JsonSpec = serde_json::from_reader(reader)?;
+    let network: NetworkSpec = spec.network.parse()?;
+
+    if spec.hosts.is_empty() {
+        return Err(SubnetError::InvalidImport(
+            "No host numbers found in the file".to_string(),
+        ));
+    }
+    if spec.hosts.len() > max_subnets {
+        return Err(SubnetError::TooManyImportedSubnets { max: max_subnets });
+    }
+
+    let mut host_requirements = Vec::with_capacity(spec.hosts.len());
+    let mut names = Vec::with_capacity(spec.hosts.len());
+    let mut extra = Vec::with_capacity(spec.hosts.len());
+    for hosts in spec.hosts {
+        let (hosts, name, tags) = match hosts {
+            JsonHosts::Count(hosts) => (hosts, String::new(), BTreeMap::new()),
+            JsonHosts::Named { name, hosts, extra } => (hosts, name, extra),
+        };
+        host_requirements.push(HostRequirement::Hosts(hosts));
+        names.push(name);
+        extra.push(tags);
+    }
+    if names.iter().all(String::is_empty) {
+        names.clear();
+    }
+    if extra.iter().all(BTreeMap::is_empty) {
+        extra.clear();
+    }
+
+    Ok(NetworkInput {
+        ip: network.network.to_string(),
+        cidr: network.cidr,
+        host_requirements,
+        names,
+        extra,
+    })
+}
+
+/**
+ * Imports the subnets of a JSON file written by [`SaveToFile::save_json`](crate::save_file::SaveToFile::save_json) <br>
+ * Unlike [`import_json`] it reads calculated subnets, e.g. a saved plan to merge with new subnets
+ * (see [`SubnetCalculator::merge_plan`](crate::subnets_calculator::SubnetCalculator::merge_plan))
+ */
+pub fn import_subnets_json(file_path: &str) -> Result<Vec<Subnet>, SubnetError> {
+    let file = File::open(Path::new(file_path))?;
+    read_subnets_json(io::BufReader::new(file))
+}
+
+/// Reads the subnets of a JSON written by [`SaveToFile::save_json`](crate::save_file::SaveToFile::save_json) from any reader
+pub fn read_subnets_json<R: Read>(reader: R) -> Result<Vec<Subnet>, SubnetError> {
+    Ok(serde_json::from_reader(reader)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_file::write_json;
+    use crate::subnets_calculator::SubnetCalculator;
+
+    /// Helper function to write the JSON data to a temporary file and import it
+    fn import_str(file_name: &str, data: &str) -> Result<NetworkInput, SubnetError> {
+        let path = std::env::temp_dir().join(file_name);
+        std::fs::write(&path, data)?;
+        let result = import_json(path.to_str().unwrap());
+        std::fs::remove_file(&path)?;
+        result
+    }
+
+    #[test]
+    fn import_valid_spec() {
+        let input = import_str(
+            "subnetting_import_valid_spec.json",
+            r#"{ "network": "10.0.0.0/24", "hosts": [50, 20, 10] }"#,
+        )
+        .unwrap();
+        assert_eq!(input.ip, "10.0.0.0");
+        assert_eq!(input.cidr, 24);
+        assert_eq!(
+            input
+                .host_requirements
+                .iter()
+                .map(|r| r.hosts())
+                .collect::<Vec<_>>(),
+            vec![50, 20, 10]
+        );
+        assert!(input.names.is_empty());
+    }
+
+    #[test]
+    fn import_named_hosts() {
+        let input = import_str(
+            "subnetting_import_named_hosts.json",
+            r#"{ "network": "10.0.0.0/24", "hosts": [{ "name": "Sales", "hosts": 50 }, 20] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            input.host_requirements,
+            vec![HostRequirement::Hosts(50), HostRequirement::Hosts(20)]
+        );
+        assert_eq!(input.names, vec!["Sales", ""]);
+    }
+
+    #[test]
+    fn import_missing_hosts() {
+        let error = import_str(
+            "subnetting_import_missing_hosts.json",
+            r#"{ "network": "10.0.0.0/24" }"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("missing field `hosts`"));
+    }
+
+    #[test]
+    fn saved_subnets_json_round_trip() {
+        let subnets =
+            crate::subnets_calculator::calculate_vlsm("10.0.0.0", 24, &[100, 50, 2]).unwrap();
+        let mut json = Vec::new();
+        crate::save_file::write_json(&subnets, &mut json).unwrap();
+
+        let imported = read_subnets_json(json.as_slice()).unwrap();
+        assert_eq!(format!("{:?}", imported), format!("{:?}", subnets));
+    }
+
+    #[test]
+    fn read_over_max_subnets() {
+        let error = read_json_with_max(
+            r#"{ "network": "10.0.0.0/16", "hosts": [50, 20, 10] }"#.as_bytes(),
+            2,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            SubnetError::TooManyImportedSubnets { max: 2 }
+        ));
+    }
+
+    #[test]
+    fn import_malformed_cidr() {
+        let error = import_str(
+            "subnetting_import_malformed_cidr.json",
+            r#"{ "network": "10.0.0.0/abc", "hosts": [50] }"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Invalid CIDR: abc is not a number");
+    }
+
+    #[test]
+    fn tags_survive_import_export_round_trip() {
+        let input = read_json(
+            r#"{ "network": "10.0.0.0/24", "hosts": [
+                { "name": "Sales", "hosts": 50, "extra": { "vlan": "10", "site": "HQ" } },
+                { "name": "DMZ", "hosts": 10 }
+            ] }"#
+                .as_bytes(),
+        )
+        .unwrap();
+        let mut calculator = SubnetCalculator::from_requirements(&input.host_requirements);
+        calculator.names = input.names;
+        calculator.extra = input.extra;
+        calculator.calculate(&input.ip, input.cidr).unwrap();
+
+        let mut json = Vec::new();
+        write_json(&calculator.subnets, &mut json).unwrap();
+        let subnets = read_subnets_json(json.as_slice()).unwrap();
+
+        let tags = BTreeMap::from([
+            ("site".to_string(), "HQ".to_string()),
+            ("vlan".to_string(), "10".to_string()),
+        ]);
+        assert_eq!(subnets[0].extra, tags);
+        assert!(subnets[1].extra.is_empty());
+
+        // The tags are written again untouched, and only to the JSON
+        let mut exported = Vec::new();
+        write_json(&subnets, &mut exported).unwrap();
+        assert_eq!(exported, json);
+        let mut csv = Vec::new();
+        crate::save_file::write_csv(&subnets, &mut csv).unwrap();
+        assert!(!String::from_utf8(csv).unwrap().contains("HQ"));
+    }
+}
@@ -0,0 +1,55 @@
+//! Library crate of the subnet calculator <br>
+//! It exposes the subnet calculations, the CSV import and the file export
+//! so they can be used without the interactive CLI <br>
+//! Without the default `std` feature only the pure arithmetic of [`math`] is available, for `no_std` targets
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub mod color;
+#[cfg(feature = "std")]
+pub mod csv_import;
+#[cfg(feature = "std")]
+pub mod input;
+#[cfg(feature = "std")]
+pub mod json_import;
+pub mod math;
+#[cfg(feature = "std")]
+pub mod save_file;
+#[cfg(feature = "std")]
+pub mod subnet;
+#[cfg(feature = "std")]
+pub mod subnet6;
+#[cfg(feature = "std")]
+pub mod subnets_calculator;
+#[cfg(all(feature = "std", any(feature = "wasm", target_arch = "wasm32")))]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+pub use csv_import::{
+    import_csv, import_csv_with_max, import_subnets_csv, read_csv, read_csv_with_max,
+    read_subnets_csv, NetworkInput, DEFAULT_MAX_SUBNETS,
+};
+#[cfg(feature = "std")]
+pub use json_import::{
+    import_json, import_json_with_max, import_subnets_json, read_json, read_json_with_max,
+    read_subnets_json,
+};
+#[cfg(feature = "std")]
+pub use save_file::{
+    write_csv, write_csv_with, write_dhcpd, write_html, write_json, write_md, write_prefixes,
+    write_template, write_yaml, CsvOptions, SaveToFile,
+};
+#[cfg(feature = "std")]
+pub use subnet::{
+    AclStyle, AddressScope, GatewayPolicy, HostRequirement, NetworkSpec, Subnet, SubnetBuilder,
+    SubnetError, Verbosity,
+};
+#[cfg(feature = "std")]
+pub use subnet6::Subnet6;
+#[cfg(feature = "std")]
+pub use subnets_calculator::{
+    aggregate, calculate_vlsm, check_plan, find_subnet, plan_totals, FillDirection,
+    PackingStrategy, PackingWaste, PlanTotals, PrefixPreview, SortOrder, SubnetCalculator,
+    Utilization,
+};
@@ -1,71 +1,857 @@
-use std::io::{self, Write};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-mod csv_import;
-mod save_file;
-mod subnet;
-mod subnets_calculator;
+use clap::{ArgGroup, Parser, ValueEnum};
+use subnetting::color::{colorize_subnet, colors_enabled, paint, Color};
+use subnetting::input::{read_answer, LineEditor};
+use subnetting::{
+    aggregate, check_plan, find_subnet, import_csv_with_max, import_json_with_max,
+    import_subnets_csv, import_subnets_json, plan_totals, read_csv_with_max, read_json_with_max,
+    write_csv, write_dhcpd, write_json, write_md, write_prefixes, write_template, write_yaml,
+    FillDirection, GatewayPolicy, HostRequirement, NetworkInput, NetworkSpec, PackingStrategy,
+    SaveToFile, SortOrder, Subnet, Subnet6, SubnetCalculator, SubnetError, Verbosity,
+    DEFAULT_MAX_SUBNETS,
+};
 
-use csv_import::import_csv;
-use save_file::SaveToFile;
-use subnet::SubnetError;
-use subnets_calculator::SubnetCalculator;
+/// Maximum number of attempts to enter a valid value before giving up
+const MAX_INPUT_ATTEMPTS: u32 = 3;
+
+/// Whether the prompts and the progress messages are hidden, see [`Args::quiet`]
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Line editor of the answers when the standard input is a terminal, `None` to read the standard input as is
+    static EDITOR: RefCell<Option<LineEditor>> =
+        RefCell::new(io::stdin().is_terminal().then(LineEditor::new).and_then(Result::ok));
+}
+
+/**
+ * Prints a prompt to the standard output, unless [`QUIET`] is set <br>
+ * On a terminal the prompt is shown by the line editor before the answer instead, see [`get_input`]
+ */
+macro_rules! prompt {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            let text = format!($($arg)*);
+            EDITOR.with_borrow_mut(|editor| match editor {
+                Some(editor) => editor.prompt(&text),
+                None => print!("{}", text),
+            });
+        }
+    };
+}
+
+/// Same as [`prompt!`], with a newline
+macro_rules! promptln {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Command line arguments, the interactive mode is used when no network is supplied
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    group(ArgGroup::new("input").args(["network", "stdin"])),
+    group(ArgGroup::new("sizes").args(["hosts", "split"]))
+)]
+struct Args {
+    /// Network address with CIDR notation (e.g. 192.168.1.0/24 or 2001:db8::/48)
+    #[arg(short, long, requires = "sizes")]
+    network: Option<String>,
+    /// Comma-separated number of hosts or prefix length for each subnet (e.g. 50,20,/28)
+    #[arg(long, value_delimiter = ',', requires = "network")]
+    hosts: Vec<HostRequirement>,
+    /// Split the network into --count subnets of this prefix length (e.g. 64), the only way to subnet an IPv6 network
+    #[arg(long, value_name = "PREFIX", requires_all = ["network", "count"])]
+    split: Option<u32>,
+    /// Number of subnets of --split
+    #[arg(long, requires = "split")]
+    count: Option<u32>,
+    /// Read the network and the hosts from the standard input, in the same CSV or JSON format as the imported files
+    #[arg(long)]
+    stdin: bool,
+    /// Maximum number of subnets of an imported file (or of the standard input), to reject huge or malicious files
+    #[arg(long, default_value_t = DEFAULT_MAX_SUBNETS)]
+    max_subnets: usize,
+    /// Collapse the imported subnets repeating an earlier subnet with the same name and hosts, instead of warning about them
+    #[arg(long)]
+    dedupe: bool,
+    /// File to save the results to, the format is inferred from the extension (.csv, .md, .json, .yaml, .html, .txt, .conf for ISC dhcpd)
+    #[arg(short, long, requires = "input")]
+    output: Option<String>,
+    /// CSV or JSON file of the subnets already allocated in the network (as saved by this tool), the new subnets are placed in the free gaps
+    #[arg(long, requires = "input")]
+    allocated: Option<String>,
+    /// Output the subnets of --allocated, unchanged, with the new ones in network order, instead of only the new ones
+    #[arg(long, requires = "allocated")]
+    merge: bool,
+    /// Allocate a /31 point-to-point link (RFC 3021) for 2 hosts and a /32 for a single host
+    #[arg(long, requires = "input")]
+    point_to_point: bool,
+    /// Position of the gateway among the usable hosts (first or last), SUBNET_GATEWAY sets the default
+    #[arg(short, long, env = "SUBNET_GATEWAY", default_value = "last")]
+    gateway: GatewayPolicy,
+    /// Strategy to pack the subnets in the network (largest-first, smallest-first or best-fit)
+    #[arg(long, default_value = "largest-first")]
+    packing: PackingStrategy,
+    /// Prefix length of the boundary each subnet starts on (e.g. 24), the addresses up to the boundary are left free
+    #[arg(long, value_name = "PREFIX")]
+    align: Option<u32>,
+    /// Address of the network the allocation starts at (e.g. 10.0.0.64), the addresses before it are left free
+    #[arg(long, value_name = "ADDRESS")]
+    start_at: Option<Ipv4Addr>,
+    /// End of the network the subnets are allocated from (bottom-up or top-down)
+    #[arg(long, default_value = "bottom-up")]
+    fill: FillDirection,
+    /// Also print the addresses wasted by each packing strategy, to help choosing one
+    #[arg(long)]
+    compare_packing: bool,
+    /// Order of the subnets in the results (input, network or size)
+    #[arg(short, long, default_value = "size")]
+    sort: SortOrder,
+    /// Amount of detail of the table (compact, normal or detailed), see --verbose for the detailed one
+    #[arg(long, default_value = "normal", conflicts_with = "verbose")]
+    verbosity: Verbosity,
+    /// Same as --verbosity detailed: also print the network and the mask in binary, the addresses as integers, the block size and the borrowed bits
+    #[arg(short, long)]
+    verbose: bool,
+    /// Also print the steps of the calculation of each subnet, from the hosts to the range of addresses
+    #[arg(long)]
+    explain: bool,
+    /// Format of the results printed to the standard output, SUBNET_FORMAT sets the default
+    #[arg(short, long, value_enum, env = "SUBNET_FORMAT", default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+    /// Print each subnet with a template instead of the format (even one set by SUBNET_FORMAT), e.g. "{network}/{prefix} gw={gateway}"
+    #[arg(short, long)]
+    template: Option<String>,
+    /// When to color the results printed as a table
+    #[arg(long, value_enum, default_value_t = ColorMode::Auto)]
+    color: ColorMode,
+    /// Convert a mask to a CIDR (e.g. 255.255.240.0) or a CIDR to a mask (e.g. /20) and exit
+    #[arg(long, conflicts_with = "input")]
+    convert: Option<String>,
+    /// Print the facts (mask, broadcast, range, hosts) of a network in CIDR notation (e.g. 192.168.5.0/26) and exit
+    #[arg(long, value_name = "NETWORK", conflicts_with_all = ["input", "convert"])]
+    describe: Option<String>,
+    /// Check that a plan saved by this tool (.csv or .json) is consistent without recalculating it, and exit
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["input", "convert", "describe"])]
+    check: Option<String>,
+    /// Aggregate comma-separated prefixes (e.g. 10.0.0.0/25,10.0.0.128/25) into the fewest covering prefixes and exit
+    #[arg(
+        long,
+        value_name = "PREFIXES",
+        value_delimiter = ',',
+        conflicts_with_all = ["input", "convert", "describe", "check"]
+    )]
+    aggregate: Vec<NetworkSpec>,
+    /// Parent network the subnets checked with --check must be inside (e.g. 10.0.0.0/24)
+    #[arg(long, value_name = "NETWORK", requires = "check")]
+    parent: Option<NetworkSpec>,
+    /// Warn about the subnets wasting more than this percentage of their addresses
+    #[arg(long, default_value_t = 50.0)]
+    waste_threshold: f64,
+    /// Print the errors as JSON objects (e.g. {"error":"InvalidCidr","detail":"33"}) to the standard error
+    #[arg(long)]
+    json_errors: bool,
+    /// Hide the prompts and the progress messages, only the results are printed
+    #[arg(short, long)]
+    quiet: bool,
+    /// Print the time taken to calculate the subnets and the subnets calculated per second to the standard error
+    #[arg(long)]
+    benchmark: bool,
+}
+
+impl Args {
+    /// Returns the verbosity of the table, [`Verbosity::Detailed`] if [`Args::verbose`] is set
+    fn verbosity(&self) -> Verbosity {
+        if self.verbose {
+            Verbosity::Detailed
+        } else {
+            self.verbosity
+        }
+    }
+}
+
+/// When to color the results printed to the standard output
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorMode {
+    /// Only if the standard output is a terminal and NO_COLOR isn't set
+    Auto,
+    /// Always color the results
+    Always,
+    /// Never color the results
+    Never,
+}
+
+/// Formats of the results printed to the standard output
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable list of the subnets
+    Table,
+    /// JSON array, same as the .json files
+    Json,
+    /// CSV with a header row, same as the .csv files
+    Csv,
+    /// Markdown tables, same as the .md files
+    Md,
+    /// YAML sequence, same as the .yaml files
+    Yaml,
+    /// One network/cidr prefix per line, same as the .txt files
+    Prefixes,
+    /// ISC dhcpd subnet stanzas, same as the .conf files
+    Dhcpd,
+    /// One line per subnet with the prefix, the usable hosts, the broadcast and the gateway
+    Summary,
+}
 
 /**
  * Main function with the CLI interface <br>
- * The user can choose to enter the network information manually or import it from a CSV file <br>
- * The user can save the results to a file in CSV or Markdown format
+ * If the network and the hosts are supplied as arguments (or through the standard input)
+ * the results are calculated without prompts, otherwise the interactive mode is started
+ */
+fn main() -> ExitCode {
+    let args = Args::parse();
+    QUIET.store(args.quiet, Ordering::Relaxed);
+
+    if let Some(value) = &args.convert {
+        return match convert_mask(value) {
+            Ok(converted) => {
+                println!("{}", converted);
+                ExitCode::SUCCESS
+            }
+            Err(e) => report_error(e, args.json_errors),
+        };
+    }
+
+    if !args.aggregate.is_empty() {
+        let prefixes: Vec<_> = args
+            .aggregate
+            .iter()
+            .map(|spec| (spec.network, spec.cidr))
+            .collect();
+        for (network, cidr) in aggregate(&prefixes) {
+            println!("{}", NetworkSpec { network, cidr });
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let result = match (&args.check, &args.describe, &args.network) {
+        (Some(file_path), _, _) => run_check(file_path, args.parent),
+        (None, Some(network), _) => run_describe(network, &args),
+        (None, None, Some(network)) => run_non_interactive(network, &args),
+        (None, None, None) if args.stdin => run_stdin(&args),
+        (None, None, None) => run_interactive(&args),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => report_error(e, args.json_errors),
+    }
+}
+
+/**
+ * Helper function to print an error to the standard error and return its exit code <br>
+ * The exit code is [`SubnetError::exit_code`]
+ */
+fn report_error(error: SubnetError, json: bool) -> ExitCode {
+    if json {
+        eprintln!("{}", error.to_json());
+    } else {
+        eprintln!("Error: {}", error);
+    }
+
+    ExitCode::from(error.exit_code())
+}
+
+/**
+ * Describes a known network without allocating subnets, see [`Subnet::describe`] <br>
+ * The subnet is printed in the format chosen with the arguments
+ */
+fn run_describe(network: &str, args: &Args) -> Result<(), SubnetError> {
+    let subnet = Subnet::describe(network)?;
+    print_formatted(&[subnet], args)
+}
+
+/**
+ * Checks a plan saved by the tool without recalculating it, see [`check_plan`] <br>
+ * Each discrepancy is printed to the standard error and [`SubnetError::InvalidPlan`] is returned if there are any
+ */
+fn run_check(file_path: &str, parent: Option<NetworkSpec>) -> Result<(), SubnetError> {
+    let subnets = import_plan(file_path)?;
+    let discrepancies = check_plan(&subnets, parent);
+    for (i, discrepancy) in &discrepancies {
+        eprintln!("Subnet #{}: {}", i + 1, discrepancy);
+    }
+
+    if !discrepancies.is_empty() {
+        return Err(SubnetError::InvalidPlan {
+            discrepancies: discrepancies.len(),
+        });
+    }
+    println!(
+        "{}: {} subnets checked, no discrepancies",
+        file_path,
+        subnets.len()
+    );
+    Ok(())
+}
+
+/**
+ * Runs the calculations with the network and hosts supplied as arguments <br>
+ * The results are saved to the output file if supplied, otherwise they're printed
+ */
+fn run_non_interactive(network: &str, args: &Args) -> Result<(), SubnetError> {
+    let (ip, cidr) = parse_network(network)?;
+    let (Some(prefix), Some(count)) = (args.split, args.count) else {
+        return calculate_and_output(&ip, cidr, &args.hosts, &[], &[], args);
+    };
+
+    if ip.parse::<Ipv6Addr>().is_ok() {
+        return print_ipv6_split(&Subnet6::new(&ip, cidr)?.split(prefix, count.into())?, args);
+    }
+    let prefix: HostRequirement = format!("/{}", prefix).parse()?;
+    calculate_and_output(&ip, cidr, &vec![prefix; count as usize], &[], &[], args)
+}
+
+/**
+ * Helper function to print the subnets of an IPv6 network split with --split <br>
+ * They're printed as a table or as a JSON array, the other formats and the output files only apply to IPv4
+ */
+fn print_ipv6_split(subnets: &[Subnet6], args: &Args) -> Result<(), SubnetError> {
+    if args.output.is_some() || !matches!(args.format, OutputFormat::Table | OutputFormat::Json) {
+        return Err(SubnetError::InvalidChoice(
+            "IPv6 subnets can only be printed as a table or as JSON".to_string(),
+        ));
+    }
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(subnets)?),
+        _ => print_ipv6_subnets(subnets),
+    }
+    Ok(())
+}
+
+/// Helper function to print the IPv6 subnets as a numbered list, like the IPv4 table
+fn print_ipv6_subnets(subnets: &[Subnet6]) {
+    for (i, field) in subnets.iter().enumerate() {
+        println!("\n#{}: {}", i + 1, field);
+        println!("{}", "-".repeat(50));
+    }
+}
+
+/**
+ * Runs the calculations with the network and hosts read from the standard input <br>
+ * The input is parsed as JSON if it starts with `{`, otherwise as CSV, see [`read_json_with_max`] and [`read_csv_with_max`]
+ */
+fn run_stdin(args: &Args) -> Result<(), SubnetError> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+
+    let mut input = if input.trim_start().starts_with('{') {
+        read_json_with_max(input.as_bytes(), args.max_subnets)?
+    } else {
+        read_csv_with_max(input.as_bytes(), args.max_subnets)?
+    };
+    handle_duplicates(&mut input, args.dedupe);
+    calculate_and_output(
+        &input.ip,
+        input.cidr,
+        &input.host_requirements,
+        &input.names,
+        &input.extra,
+        args,
+    )
+}
+
+/**
+ * Helper function to calculate the subnets without prompts <br>
+ * The results are saved to the output file if supplied, otherwise they're printed
+ */
+fn calculate_and_output(
+    ip: &str,
+    cidr: u32,
+    num_hosts_array: &[HostRequirement],
+    names: &[String],
+    extra: &[BTreeMap<String, String>],
+    args: &Args,
+) -> Result<(), SubnetError> {
+    let mut calculator = SubnetCalculator::from_requirements(num_hosts_array);
+    calculator.point_to_point = args.point_to_point;
+    calculator.gateway_policy = args.gateway;
+    calculator.packing = args.packing;
+    calculator.align = args.align;
+    calculator.start_at = args.start_at;
+    calculator.fill_direction = args.fill;
+    calculator.names = names.to_vec();
+    calculator.extra = extra.to_vec();
+    let allocated = match &args.allocated {
+        Some(file_path) => Some(import_plan(file_path)?),
+        None => None,
+    };
+
+    let start = Instant::now();
+    let merged = match &allocated {
+        Some(allocated) if args.merge => Some(calculator.merge_plan(ip, cidr, allocated)?),
+        Some(allocated) => {
+            calculator.calculate_in_pool(ip, cidr, allocated)?;
+            None
+        }
+        None => {
+            calculator.calculate(ip, cidr)?;
+            None
+        }
+    };
+    if args.benchmark {
+        report_benchmark(calculator.subnets.len(), start.elapsed());
+    }
+    warn_overlaps(&calculator);
+    warn_waste(&calculator, args.waste_threshold);
+    if args.compare_packing {
+        report_packing(&calculator);
+    }
+
+    let subnets = merged.unwrap_or_else(|| sort_results(&calculator, args.sort));
+    match &args.output {
+        Some(file_name) => save_to_file(file_name, &subnets)?,
+        None => print_formatted(&subnets, args)?,
+    }
+
+    Ok(())
+}
+
+/**
+ * Helper function to import the subnets of a plan saved by the tool, see [`import_subnets_json`] and [`import_subnets_csv`] <br>
+ * The `.json` files are imported as JSON, the other files as CSV
+ */
+fn import_plan(file_path: &str) -> Result<Vec<Subnet>, SubnetError> {
+    if file_path.to_lowercase().ends_with(".json") {
+        import_subnets_json(file_path)
+    } else {
+        Ok(import_subnets_csv(file_path)?)
+    }
+}
+
+/**
+ * Runs the interactive mode <br>
+ * The user can choose to enter the network information manually or import it from a CSV or JSON file <br>
+ * The user can save the results to a file in CSV, Markdown, JSON, YAML, HTML or prefix list format
  */
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Choose an option:");
-    println!("1. Enter network information manually");
-    println!("2. Import from CSV file");
+fn run_interactive(args: &Args) -> Result<(), SubnetError> {
+    promptln!("Choose an option:");
+    promptln!("1. Enter network information manually");
+    promptln!("2. Import from CSV or JSON file");
+    promptln!("3. Split a network into equal subnets (FLSM)");
+    promptln!("4. Count the subnets of a given size that fit in a network");
+    promptln!("5. Convert a mask to a CIDR or a CIDR to a mask");
+    promptln!("6. Describe a network without subnetting it");
+    promptln!("7. Find the subnet of an address in a saved plan");
 
-    print!("Enter your choice: ");
+    prompt!("Enter your choice: ");
     let choice = get_input()?;
 
-    let (ip, cidr, num_hosts_array) = match choice.as_str() {
+    let input = match choice.as_str() {
         "1" => {
             let (ip, cidr) = get_network_input()?;
+            if ip.parse::<Ipv6Addr>().is_ok() {
+                return split_ipv6(&ip, cidr);
+            }
             let num_subnets = get_num_subnets()?;
-            let num_hosts_array = get_num_hosts(num_subnets)?;
-            (ip, cidr, num_hosts_array)
+            let (host_requirements, names) = get_num_hosts(num_subnets)?;
+            NetworkInput {
+                ip,
+                cidr,
+                host_requirements,
+                names,
+                extra: Vec::new(),
+            }
         }
         "2" => {
-            print!("Enter the path to the CSV file: ");
+            prompt!("Enter the path to the CSV or JSON file: ");
             let file_path = get_input()?;
-            import_csv(&file_path)?
+            let mut input = if file_path.to_lowercase().ends_with(".json") {
+                import_json_with_max(&file_path, args.max_subnets)?
+            } else {
+                import_csv_with_max(&file_path, args.max_subnets)?
+            };
+            handle_duplicates(&mut input, args.dedupe);
+            input
+        }
+        "3" => {
+            let (ip, cidr) = get_network_input()?;
+            let num_subnets = get_num_subnets()?;
+            let subnets = SubnetCalculator::split_equal(&ip, cidr, num_subnets)?;
+            return show_results(&subnets, args);
+        }
+        "4" => {
+            let (_, cidr) = get_network_input()?;
+            prompt!("\nEnter the CIDR of the subnets (e.g. 24): ");
+            let child_cidr = get_input()?.parse()?;
+            let count = SubnetCalculator::count_subnets(cidr, child_cidr)?;
+            println!(
+                "\n{} /{} subnets fit in a /{} network",
+                count, child_cidr, cidr
+            );
+            return Ok(());
+        }
+        "5" => {
+            prompt!("\nEnter the mask (e.g. 255.255.240.0) or the CIDR (e.g. /20): ");
+            println!("{}", convert_mask(&get_input()?)?);
+            return Ok(());
+        }
+        "6" => {
+            let (ip, cidr) = get_network_input()?;
+            let subnet = Subnet::describe(&format!("{}/{}", ip, cidr))?;
+            return show_results(&[subnet], args);
         }
-        _ => return Err("Invalid choice".into()),
+        "7" => return lookup_address(),
+        choice => return Err(SubnetError::InvalidChoice(choice.to_string())),
     };
 
-    let mut calculator = SubnetCalculator::new(num_hosts_array);
-    calculator.calculate(&ip, cidr)?;
+    let mut calculator = SubnetCalculator::from_requirements(&input.host_requirements);
+    calculator.gateway_policy = args.gateway;
+    calculator.packing = args.packing;
+    calculator.align = args.align;
+    calculator.start_at = args.start_at;
+    calculator.fill_direction = args.fill;
+
+    promptln!("\nSizing preview:");
+    for preview in calculator.preview()? {
+        promptln!("\t - {}", preview);
+    }
+
+    calculator.names = input.names;
+    calculator.extra = input.extra;
+    let start = Instant::now();
+    calculator.calculate(&input.ip, input.cidr)?;
+    if args.benchmark {
+        report_benchmark(calculator.subnets.len(), start.elapsed());
+    }
+
+    // The results are shown again after each edit of the host requirements
+    loop {
+        warn_overlaps(&calculator);
+        warn_waste(&calculator, args.waste_threshold);
+        if args.compare_packing {
+            report_packing(&calculator);
+        }
+        let subnets = sort_results(&calculator, args.sort);
+        print_formatted(&subnets, args)?;
+        promptln!("\nUtilization: {}", calculator.utilization());
+
+        let mut requirements = calculator.requirements();
+        let mut edited_names = calculator.names.clone();
+        let mut edited_extra = calculator.extra.clone();
+        if !matches!(
+            prompt_edit(&mut requirements, &mut edited_names, &mut edited_extra),
+            Ok(true)
+        ) {
+            if let Ok(true) = prompt_save() {
+                save_results(&subnets)?;
+            }
+            return Ok(());
+        }
+
+        let previous_names = std::mem::replace(&mut calculator.names, edited_names);
+        let previous_extra = std::mem::replace(&mut calculator.extra, edited_extra);
+        if let Err(e) = calculator.recalculate(&requirements) {
+            calculator.names = previous_names;
+            calculator.extra = previous_extra;
+            eprintln!("Error: {}, the previous subnets are kept", e);
+        }
+    }
+}
+
+/**
+ * Helper function to find the subnet of an address in the subnets of a CSV file saved by the tool,
+ * see [`find_subnet`] <br>
+ * It prints the number of the subnet in the file and its name (if any), or that no subnet contains the address
+ */
+fn lookup_address() -> Result<(), SubnetError> {
+    prompt!("Enter the path to the CSV or JSON file of the subnets: ");
+    let subnets = import_plan(&get_input()?)?;
 
-    print_results(&calculator.subnets);
+    prompt!("Enter the IP address to look up (e.g. 10.0.0.45): ");
+    let input = get_input()?;
+    let ip = input
+        .parse()
+        .map_err(|_| SubnetError::InvalidIpAddress(input.clone()))?;
+
+    match find_subnet(&subnets, ip) {
+        Some((index, subnet)) => match &subnet.name {
+            Some(name) => println!(
+                "{} is in subnet #{} ({}): {}",
+                ip,
+                index + 1,
+                name,
+                subnet.summary()
+            ),
+            None => println!("{} is in subnet #{}: {}", ip, index + 1, subnet.summary()),
+        },
+        None => println!("{} isn't in any subnet of the plan", ip),
+    }
+
+    Ok(())
+}
+
+/**
+ * Helper function to let the user change, add or remove a host requirement after seeing the results <br>
+ * It returns true if the requirements (and the names and the tags, if any) have been edited, false to keep the results
+ */
+fn prompt_edit(
+    requirements: &mut Vec<HostRequirement>,
+    names: &mut Vec<String>,
+    extra: &mut Vec<BTreeMap<String, String>>,
+) -> io::Result<bool> {
+    promptln!("\nDo you want to edit the subnets?");
+    promptln!("1. Change the hosts of a subnet");
+    promptln!("2. Add a subnet");
+    promptln!("3. Remove a subnet");
+    promptln!("4. Keep the results");
+
+    prompt!("Enter your choice: ");
+    match get_input()?.as_str() {
+        "1" => {
+            let index = get_subnet_index(requirements, names)?;
+            requirements[index] = get_subnet_hosts(index as u32 + 1)?;
+        }
+        "2" => {
+            requirements.push(get_subnet_hosts(requirements.len() as u32 + 1)?);
+            if !names.is_empty() {
+                prompt!("Enter the name of the subnet: ");
+                names.push(get_input()?);
+            }
+        }
+        "3" => {
+            let index = get_subnet_index(requirements, names)?;
+            requirements.remove(index);
+            if !names.is_empty() {
+                names.remove(index);
+            }
+            if index < extra.len() {
+                extra.remove(index);
+            }
+        }
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/**
+ * Helper function to print the host requirements in input order and get user input of one of them <br>
+ * It returns the index of the requirement
+ */
+fn get_subnet_index(requirements: &[HostRequirement], names: &[String]) -> io::Result<usize> {
+    for (i, requirement) in requirements.iter().enumerate() {
+        let size = match requirement {
+            HostRequirement::Hosts(hosts) => format!("{} hosts", hosts),
+            HostRequirement::Prefix(prefix) => format!("/{}", prefix),
+            HostRequirement::Growth { hosts, grown } => {
+                format!("{} hosts plus growth ({} hosts)", hosts, grown)
+            }
+        };
+        match names.get(i) {
+            Some(name) => promptln!("#{} ({}): {}", i + 1, name, size),
+            None => promptln!("#{}: {}", i + 1, size),
+        }
+    }
+
+    prompt!("Enter the number of the subnet: ");
+    match get_input()?.parse::<usize>() {
+        Ok(number) if (1..=requirements.len()).contains(&number) => Ok(number - 1),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Invalid subnet number",
+        )),
+    }
+}
+
+/**
+ * Helper function to sort the calculated subnets in the given order
+ * see [`SubnetCalculator::sorted_positions`]
+ */
+fn sort_results(calculator: &SubnetCalculator, order: SortOrder) -> Vec<Subnet> {
+    calculator
+        .sorted_positions(order)
+        .iter()
+        .map(|&i| calculator.subnets[i].clone())
+        .collect()
+}
+
+/**
+ * Helper function to collapse the duplicate imported subnets if asked, otherwise to print a warning for each of them
+ * see [`NetworkInput::duplicates`] and [`NetworkInput::dedupe`]
+ */
+fn handle_duplicates(input: &mut NetworkInput, dedupe: bool) {
+    if dedupe {
+        let collapsed = input.dedupe();
+        if collapsed > 0 {
+            eprintln!("Collapsed {} duplicate subnet(s)", collapsed);
+        }
+        return;
+    }
+    for i in input.duplicates() {
+        eprintln!(
+            "Warning: subnet #{} ({}) repeats an earlier subnet, use --dedupe to collapse it",
+            i + 1,
+            input.names[i]
+        );
+    }
+}
+
+/**
+ * Helper function to print a warning for each pair of overlapping subnets
+ * see [`SubnetCalculator::find_overlaps`]
+ */
+fn warn_overlaps(calculator: &SubnetCalculator) {
+    for (a, b) in calculator.find_overlaps() {
+        eprintln!("Warning: subnet #{} overlaps subnet #{}", a + 1, b + 1);
+    }
+}
+
+/**
+ * Helper function to print a warning for each subnet wasting more than the threshold of its addresses
+ * see [`SubnetCalculator::find_wasteful`]
+ */
+fn warn_waste(calculator: &SubnetCalculator, threshold_percent: f64) {
+    for (i, wasted) in calculator.find_wasteful(threshold_percent) {
+        eprintln!(
+            "Warning: subnet #{} wastes {} addresses (more than {}%)",
+            i + 1,
+            wasted,
+            threshold_percent
+        );
+    }
+}
+
+/**
+ * Helper function to print the addresses wasted by each packing strategy in the parent network of the calculator
+ * to the standard error, see [`SubnetCalculator::compare_packing`]
+ */
+fn report_packing(calculator: &SubnetCalculator) {
+    let (Some(network), Some(cidr)) = (calculator.parent_network(), calculator.parent_cidr())
+    else {
+        return;
+    };
+    for (packing, waste) in calculator.compare_packing(&network.to_string(), cidr) {
+        match waste {
+            Ok(waste) => eprintln!("Packing {}: {}", packing, waste),
+            Err(e) => eprintln!("Packing {}: {}", packing, e),
+        }
+    }
+}
+
+/**
+ * Helper function to print the time taken to calculate the subnets to the standard error, see [`Args::benchmark`] <br>
+ * e.g. `Benchmark: 3 subnets calculated in 0.012 ms (250000 subnets/s, 4.000 µs per subnet)`
+ */
+fn report_benchmark(subnets: usize, elapsed: Duration) {
+    // The clock may not tick for a few subnets, the rate is capped instead of dividing by zero
+    let seconds = elapsed.as_secs_f64().max(f64::from(u32::MAX).recip());
+    eprintln!(
+        "Benchmark: {} subnets calculated in {:.3} ms ({:.0} subnets/s, {:.3} µs per subnet)",
+        subnets,
+        elapsed.as_secs_f64() * 1e3,
+        subnets as f64 / seconds,
+        elapsed.as_secs_f64() * 1e6 / subnets.max(1) as f64
+    );
+}
+
+/**
+ * Helper function to print the results and save them to a file if the user wants to
+ */
+fn show_results(subnets: &[Subnet], args: &Args) -> Result<(), SubnetError> {
+    print_formatted(subnets, args)?;
 
     if let Ok(true) = prompt_save() {
-        save_results(&calculator.subnets)?;
+        save_results(subnets)?;
     }
 
     Ok(())
 }
 
 /**
- * Helper functions to get user input of the [`subnet::Subnet::network`] and [`subnet::Subnet::cidr`] <br>
- * It handles the IO errors and returns the input as a String
+ * Helper functions to get user input of the [`Subnet::network`] and [`Subnet::cidr`] <br>
+ * It handles the IO errors and returns the input as a String <br>
+ * An IPv4 address without a CIDR gets the classful default prefix of its class, see [`NetworkSpec::parse_classful`]
  */
 fn get_network_input() -> Result<(String, u32), SubnetError> {
-    print!("\nEnter the network address with CIDR notation (e.g. 192.168.1.0/24): ");
+    prompt!(
+        "\nEnter the network address with CIDR notation (e.g. 192.168.1.0/24 or 2001:db8::/48): "
+    );
     let address = get_input()?;
-    let ip_cidr: Vec<&str> = address.split('/').collect();
-    if ip_cidr.len() != 2 {
-        return Err(SubnetError::InvalidIpAddress(address));
+    match parse_network(&address) {
+        Err(SubnetError::MissingCidr(_)) => {
+            let spec = NetworkSpec::parse_classful(&address)?;
+            promptln!("No CIDR given, using the classful default /{}", spec.cidr);
+            Ok((spec.network.to_string(), spec.cidr))
+        }
+        result => result,
     }
-    let ip = ip_cidr[0].to_string();
-    let cidr = ip_cidr[1]
+}
+
+/**
+ * Helper function to split an address in CIDR notation (e.g. 192.168.1.0/24) into
+ * the [`Subnet::network`] and the [`Subnet::cidr`], see [`NetworkSpec`] <br>
+ * IPv6 addresses are returned as they are, their CIDR is validated by [`Subnet6::new`]
+ */
+fn parse_network(address: &str) -> Result<(String, u32), SubnetError> {
+    if let Some((ip, cidr)) = address.split_once('/') {
+        if ip.trim().parse::<Ipv6Addr>().is_ok() {
+            let cidr = cidr
+                .trim()
+                .parse()
+                .map_err(|_| SubnetError::InvalidCidrFormat(cidr.to_string()))?;
+            return Ok((ip.trim().to_string(), cidr));
+        }
+    }
+
+    let spec: NetworkSpec = address.parse()?;
+    Ok((spec.network.to_string(), spec.cidr))
+}
+
+/**
+ * Helper function to convert a mask (e.g. `255.255.240.0`) to a CIDR (e.g. `/20`) or a CIDR to a mask,
+ * see [`Subnet::mask_to_cidr`] and [`Subnet::cidr_to_mask`]
+ */
+fn convert_mask(value: &str) -> Result<String, SubnetError> {
+    let value = value.trim();
+    if value.contains('.') {
+        let mask = value
+            .parse()
+            .map_err(|_| SubnetError::InvalidIpAddress(value.to_string()))?;
+        return Ok(format!("/{}", Subnet::mask_to_cidr(mask)?));
+    }
+
+    let cidr = value
+        .trim_start_matches('/')
         .parse()
-        .map_err(|_| SubnetError::InvalidCidr(0))?;
-    Ok((ip, cidr))
+        .map_err(|_| SubnetError::InvalidCidrFormat(value.to_string()))?;
+    Ok(Subnet::cidr_to_mask(cidr)?.to_string())
+}
+
+/**
+ * Helper function to split an IPv6 network into equally sized subnets <br>
+ * It asks the user for the CIDR and the number of the subnets and prints them
+ */
+fn split_ipv6(ip: &str, cidr: u32) -> Result<(), SubnetError> {
+    prompt!("\nEnter the CIDR of the subnets (e.g. 64): ");
+    let new_cidr = get_input()?.parse()?;
+    let num_subnets = get_num_subnets()?;
+
+    let parent = Subnet6::new(ip, cidr)?;
+    print_ipv6_subnets(&parent.split(new_cidr, num_subnets.into())?);
+
+    Ok(())
 }
 
 /**
@@ -73,7 +859,7 @@ fn get_network_input() -> Result<(String, u32), SubnetError> {
  * It handles the IO errors and returns the input as a String
  */
 fn get_num_subnets() -> io::Result<u32> {
-    print!("\nEnter the number of subnets: ");
+    prompt!("\nEnter the number of subnets: ");
     let num_subnets = get_input()?
         .parse()
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
@@ -81,28 +867,121 @@ fn get_num_subnets() -> io::Result<u32> {
 }
 
 /**
- * Helper functions to get user input of the number of [`subnet::Subnet::hosts`] for each subnet <br>
- * It handles the IO errors and returns the input as a `Vec<u32>`
+ * Helper functions to get user input of the number of [`Subnet::hosts`] and the optional name for each subnet <br>
+ * It handles the IO errors and returns the input as a `Vec<HostRequirement>` and the names,
+ * which are empty if no subnet has been named
  */
-fn get_num_hosts(num_subnets: u32) -> io::Result<Vec<u32>> {
+fn get_num_hosts(num_subnets: u32) -> io::Result<(Vec<HostRequirement>, Vec<String>)> {
     let mut num_hosts_array = Vec::new();
+    let mut names = Vec::new();
     for i in 0..num_subnets {
-        print!("Enter the number of hosts for subnet #{}: ", i + 1);
-        let num_hosts = get_input()?
-            .parse()
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-        num_hosts_array.push(num_hosts);
+        num_hosts_array.push(get_subnet_hosts(i + 1)?);
+        prompt!(
+            "Enter the name of subnet #{} (leave empty for none): ",
+            i + 1
+        );
+        names.push(get_input()?);
+    }
+
+    if names.iter().all(String::is_empty) {
+        names.clear();
+    }
+    Ok((num_hosts_array, names))
+}
+
+/**
+ * Helper function to get user input of the number of [`Subnet::hosts`] (or the prefix length) of a single subnet <br>
+ * It asks again on invalid input and returns an error only after [`MAX_INPUT_ATTEMPTS`] failures
+ */
+fn get_subnet_hosts(subnet_number: u32) -> io::Result<HostRequirement> {
+    let mut attempts = 1;
+    loop {
+        prompt!(
+            "Enter the number of hosts (or a prefix like /28, or hosts plus growth like 50+30%) for subnet #{}: ",
+            subnet_number
+        );
+        match get_input()?.parse() {
+            Ok(requirement) => return Ok(requirement),
+            Err(e) if attempts < MAX_INPUT_ATTEMPTS => {
+                promptln!("{}, please enter a whole number or a prefix", e);
+                attempts += 1;
+            }
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
+        }
     }
-    Ok(num_hosts_array)
 }
 
 /**
- * Helper function to print the results of the subnet calculations in a easy-to-read format
+ * Helper function to print the results in the format chosen with the arguments
+ * see [`OutputFormat`], or with the template if any, see [`Subnet::render`]
  */
-fn print_results(subnets: &[subnet::Subnet]) {
+fn print_formatted(subnets: &[Subnet], args: &Args) -> Result<(), SubnetError> {
+    if let Some(template) = &args.template {
+        write_template(subnets, template, io::stdout())?;
+        return Ok(());
+    }
+
+    match args.format {
+        OutputFormat::Table => {
+            let color = match args.color {
+                ColorMode::Auto => colors_enabled(io::stdout().is_terminal()),
+                ColorMode::Always => true,
+                ColorMode::Never => false,
+            };
+            print_results(subnets, args.verbosity(), args.explain, color);
+            println!("\n{}", plan_totals(subnets));
+        }
+        OutputFormat::Json => {
+            write_json(subnets, io::stdout())?;
+            println!();
+        }
+        OutputFormat::Csv => write_csv(subnets, io::stdout())?,
+        OutputFormat::Md => write_md(subnets, io::stdout())?,
+        OutputFormat::Yaml => write_yaml(subnets, io::stdout())?,
+        OutputFormat::Prefixes => write_prefixes(subnets, io::stdout())?,
+        OutputFormat::Dhcpd => write_dhcpd(subnets, io::stdout())?,
+        OutputFormat::Summary => {
+            for subnet in subnets {
+                println!("{}", subnet.summary());
+            }
+        }
+    }
+    Ok(())
+}
+
+/**
+ * Helper function to print the results of the subnet calculations in a easy-to-read format <br>
+ * The names of the subnets are printed next to their numbers, if any <br>
+ * The amount of detail is the one of the verbosity, the compact subnets are printed one per line <br>
+ * If explain, the steps of the calculation are printed after each subnet, see [`Subnet::explain`] <br>
+ * If color, the labels, the network addresses and the wasted hosts are highlighted, see [`colorize_subnet`]
+ */
+fn print_results(subnets: &[Subnet], verbosity: Verbosity, explain: bool, color: bool) {
     for (i, field) in subnets.iter().enumerate() {
-        println!("\n#{}: {}", i + 1, field);
-        println!("{}", "-".repeat(50));
+        let label = match &field.name {
+            Some(name) => format!("#{} ({})", i + 1, name),
+            None => format!("#{}", i + 1),
+        };
+        let (label, text) = if color {
+            (
+                paint(&label, Color::Cyan),
+                colorize_subnet(field, verbosity),
+            )
+        } else {
+            (label, field.format_with(verbosity))
+        };
+
+        if verbosity == Verbosity::Compact {
+            println!("{}: {}", label, text);
+        } else {
+            println!("\n{}: {}", label, text);
+        }
+        if explain {
+            println!("Steps: {}", field.explain());
+        }
+        if verbosity != Verbosity::Compact {
+            println!("{}", "-".repeat(50));
+        }
     }
 }
 
@@ -111,25 +990,73 @@ fn print_results(subnets: &[subnet::Subnet]) {
  * It returns a boolean based on the user input
  */
 fn prompt_save() -> io::Result<bool> {
-    print!("\nDo you want to save the results? (y/n) (Supported formats: CSV (.csv), Markdown (.md)): ");
-    let save = get_input()?.to_lowercase();
-    Ok(save == "y" || save == "yes")
+    prompt!("\nDo you want to save the results? (y/n) (Supported formats: CSV (.csv), Markdown (.md), JSON (.json), YAML (.yaml), HTML (.html), prefix list (.txt), ISC dhcpd (.conf)): ");
+    let answer = get_input()?;
+    if let Some(save) = parse_answer(&answer) {
+        return Ok(save);
+    }
+
+    // An unrecognized answer is asked again once, so a typo doesn't discard the results
+    eprintln!(
+        "'{}' isn't a valid answer, please answer y (yes) or n (no)",
+        answer
+    );
+    prompt!("Do you want to save the results? (y/n): ");
+    let answer = get_input()?;
+    match parse_answer(&answer) {
+        Some(save) => Ok(save),
+        None => {
+            eprintln!(
+                "'{}' isn't a valid answer, the results aren't saved",
+                answer
+            );
+            Ok(false)
+        }
+    }
 }
 
 /**
- * Helper function to save the results to a file in CSV or Markdown format based on the file extension
- * see [`SaveToFile::save_md`] and [`SaveToFile::save_csv`]
+ * Helper function to parse a yes/no answer, ignoring the case and the surrounding whitespace <br>
+ * It returns `None` for anything but `y`, `yes`, `n` and `no` (e.g. `ya` or an empty answer)
  */
-fn save_results(subnets: &[subnet::Subnet]) -> io::Result<()> {
-    print!("Enter the file name (with the extension): ");
+fn parse_answer(answer: &str) -> Option<bool> {
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/**
+ * Helper function to save the results to a file in CSV, Markdown, JSON, YAML, HTML, prefix list or ISC dhcpd format based on the file extension
+ * see [`SaveToFile::save_md`], [`SaveToFile::save_csv`], [`SaveToFile::save_json`], [`SaveToFile::save_yaml`], [`SaveToFile::save_html`], [`SaveToFile::save_prefixes`] and [`SaveToFile::save_dhcpd`]
+ */
+fn save_results(subnets: &[Subnet]) -> io::Result<()> {
+    prompt!("Enter the file name (with the extension): ");
     let file_name = get_input()?;
-    let save = SaveToFile::new(&file_name, subnets.to_vec());
+    save_to_file(&file_name, subnets)
+}
 
-    match file_name.split('.').last().unwrap().to_lowercase().as_str() {
+/**
+ * Helper function to save the results to the given file, the format is inferred from the file extension
+ */
+fn save_to_file(file_name: &str, subnets: &[Subnet]) -> io::Result<()> {
+    let save = SaveToFile::new(file_name, subnets.to_vec());
+
+    match file_name
+        .split('.')
+        .next_back()
+        .unwrap()
+        .to_lowercase()
+        .as_str()
+    {
         "md" => save.save_md()?,
-        "csv" => save
-            .save_csv()
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?,
+        "csv" => save.save_csv().map_err(io::Error::other)?,
+        "json" => save.save_json()?,
+        "yaml" | "yml" => save.save_yaml()?,
+        "html" | "htm" => save.save_html()?,
+        "txt" => save.save_prefixes()?,
+        "conf" => save.save_dhcpd()?,
         _ => {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -138,16 +1065,40 @@ fn save_results(subnets: &[subnet::Subnet]) -> io::Result<()> {
         }
     }
 
-    println!("Results saved to {}", file_name);
+    promptln!("Results saved to {}", file_name);
     Ok(())
 }
 
 /**
- * Helper function to get user input and return it as a String
+ * Helper function to get user input from the standard input and return it as a String, see [`read_answer`] <br>
+ * On a terminal it's read with the line editor, so the previous answers can be recalled with the arrow keys <br>
+ * It returns an error if the input has ended (EOF)
  */
 fn get_input() -> io::Result<String> {
-    io::stdout().flush()?;
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    Ok(input.trim().to_string())
+    EDITOR.with_borrow_mut(|editor| match editor {
+        Some(editor) => read_answer(editor),
+        None => {
+            io::stdout().flush()?;
+            read_answer(&mut io::stdin().lock())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_ignore_case_and_whitespace() {
+        assert_eq!(parse_answer("y"), Some(true));
+        assert_eq!(parse_answer("YES"), Some(true));
+        assert_eq!(parse_answer(" No "), Some(false));
+    }
+
+    #[test]
+    fn other_answers_are_rejected() {
+        assert_eq!(parse_answer("ya"), None);
+        assert_eq!(parse_answer("yes please"), None);
+        assert_eq!(parse_answer(""), None);
+    }
 }
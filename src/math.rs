@@ -0,0 +1,274 @@
+//! Pure arithmetic of the IPv4 subnets (masks, prefix lengths, sizes and ranges) <br>
+//! The masks and the ranges are shared with the IPv6 subnets through [`AddressFamily`] <br>
+//! It only depends on `core`, so it's available without the `std` feature (e.g. for embedded or WASM targets) <br>
+//! It only uses integer math (no floating point `log2`), so the sizes are bit-for-bit identical on every platform
+
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// The number of bits in an IPv4 address
+pub const IPV4_BITS: u32 = 32;
+/// The number of bits in an IPv6 address
+pub const IPV6_BITS: u32 = 128;
+/// The minimum number of host bits of a subnet (a /30 with 2 usable hosts)
+pub const MIN_HOST_BITS: u32 = 2;
+
+/**
+ * Address family of the subnet math, so the masks and the ranges are computed the same way for IPv4 and IPv6 <br>
+ * The addresses are handled as `u128` integers, an IPv4 address being the 32 low bits <br>
+ * IPv6 has no classes, [`class_of`] and [`default_prefix`] only apply to IPv4
+ */
+pub trait AddressFamily: Copy {
+    /// The number of bits in an address
+    const BITS: u32;
+
+    /// Returns the address as an integer
+    fn to_u128(self) -> u128;
+
+    /// Returns the address of the integer, only its [`AddressFamily::BITS`] low bits are used
+    fn from_u128(bits: u128) -> Self;
+}
+
+impl AddressFamily for Ipv4Addr {
+    const BITS: u32 = IPV4_BITS;
+
+    fn to_u128(self) -> u128 {
+        u32::from(self).into()
+    }
+
+    fn from_u128(bits: u128) -> Self {
+        Ipv4Addr::from(bits as u32)
+    }
+}
+
+impl AddressFamily for Ipv6Addr {
+    const BITS: u32 = IPV6_BITS;
+
+    fn to_u128(self) -> u128 {
+        u128::from(self)
+    }
+
+    fn from_u128(bits: u128) -> Self {
+        Ipv6Addr::from(bits)
+    }
+}
+
+/// Helper function to get the integer with the [`AddressFamily::BITS`] low bits set
+fn all_ones<A: AddressFamily>() -> u128 {
+    u128::MAX >> (u128::BITS - A::BITS)
+}
+
+/**
+ * Converts a prefix length to the mask of the address family (e.g. `20` to `255.255.240.0` or `64` to `ffff:ffff:ffff:ffff::`),
+ * `None` if it's longer than the address <br>
+ * The shift is checked, since shifting by all the bits of the integer overflows
+ */
+pub fn prefix_mask<A: AddressFamily>(cidr: u32) -> Option<A> {
+    if cidr > A::BITS {
+        return None;
+    }
+    let host_bits = all_ones::<A>().checked_shr(cidr).unwrap_or(0);
+    Some(A::from_u128(all_ones::<A>() & !host_bits))
+}
+
+/// Returns the first address of the subnet of the address, the address with the host bits cleared
+pub fn network_of<A: AddressFamily>(ip: A, cidr: u32) -> A {
+    let mask = prefix_mask::<A>(cidr).map_or(all_ones::<A>(), A::to_u128);
+    A::from_u128(ip.to_u128() & mask)
+}
+
+/// Returns the last address of the subnet of the address, the address with the host bits set (the broadcast for IPv4)
+pub fn last_address<A: AddressFamily>(ip: A, cidr: u32) -> A {
+    let mask = prefix_mask::<A>(cidr).map_or(all_ones::<A>(), A::to_u128);
+    A::from_u128(ip.to_u128() | (all_ones::<A>() & !mask))
+}
+
+/// Converts a prefix length to a subnet mask (e.g. `20` to `255.255.240.0`), `None` if it's longer than /32, see [`prefix_mask`]
+pub fn cidr_to_mask(cidr: u32) -> Option<Ipv4Addr> {
+    prefix_mask(cidr)
+}
+
+/// Converts a subnet mask to a prefix length (e.g. `255.255.240.0` to `20`), `None` if the ones aren't contiguous
+pub fn mask_to_cidr(mask: Ipv4Addr) -> Option<u32> {
+    is_valid_mask(mask).then(|| u32::from(mask).leading_ones())
+}
+
+/// Returns true if the mask is a run of ones followed only by zeros (e.g. `255.255.255.0`, but not `255.255.0.255`)
+pub fn is_valid_mask(mask: Ipv4Addr) -> bool {
+    let bits = u32::from(mask);
+    bits.leading_ones() == bits.count_ones()
+}
+
+/**
+ * Returns the number of host bits needed for the hosts plus the network and broadcast addresses,
+ * i.e. `ceil(log2(hosts + 2))` with integer math <br>
+ * It's at least [`MIN_HOST_BITS`] and can be 33 for more than 2^32 - 2 hosts
+ */
+pub fn required_host_bits(hosts: u32) -> u32 {
+    let addresses = u64::from(hosts) + 2;
+    (u64::BITS - (addresses - 1).leading_zeros()).max(MIN_HOST_BITS)
+}
+
+/**
+ * Returns the prefix length of the smallest subnet with enough usable hosts for the number of hosts,
+ * `None` if it would need a prefix shorter than /0
+ */
+pub fn hosts_to_prefix(hosts: u32) -> Option<u32> {
+    IPV4_BITS.checked_sub(required_host_bits(hosts))
+}
+
+/// Returns the number of addresses of a subnet, i.e. `2^(32 - cidr)`
+pub fn addresses(cidr: u32) -> u64 {
+    1 << (IPV4_BITS - cidr)
+}
+
+/// Returns the number of usable hosts of a subnet, every address of a /31 or a /32 (RFC 3021) being usable
+pub fn usable_hosts(cidr: u32) -> u32 {
+    if IPV4_BITS - cidr < MIN_HOST_BITS {
+        addresses(cidr) as u32
+    } else {
+        (addresses(cidr) - 2) as u32
+    }
+}
+
+/// Returns the network address of the subnet of the address (e.g. `192.168.1.0` for `192.168.1.77` and /24)
+pub fn network(ip: Ipv4Addr, cidr: u32) -> Ipv4Addr {
+    network_of(ip, cidr)
+}
+
+/// Returns the broadcast address of the subnet of the address (e.g. `192.168.1.255` for `192.168.1.77` and /24)
+pub fn broadcast(ip: Ipv4Addr, cidr: u32) -> Ipv4Addr {
+    last_address(ip, cidr)
+}
+
+/// Returns the classful class of the address from its first octet
+pub fn class_of(ip: Ipv4Addr) -> char {
+    match ip.octets()[0] {
+        0..=127 => 'A',
+        128..=191 => 'B',
+        192..=223 => 'C',
+        224..=239 => 'D',
+        240..=255 => 'E',
+    }
+}
+
+/// Returns the prefix length of the classful default mask (/8 for class A, /16 for class B and /24 for class C), `None` for the classes D and E
+pub fn default_prefix(class: char) -> Option<u32> {
+    match class {
+        'A' => Some(8),
+        'B' => Some(16),
+        'C' => Some(24),
+        _ => None,
+    }
+}
+
+/**
+ * Returns true if both networks of the same prefix length differ only in the last bit of the prefix,
+ * i.e. they're the two halves of the same supernet one bit shorter (e.g. `10.0.0.0` and `10.0.0.128` for a /25)
+ */
+pub fn are_siblings(a: Ipv4Addr, b: Ipv4Addr, cidr: u32) -> bool {
+    if cidr == 0 || cidr > IPV4_BITS {
+        return false;
+    }
+    u32::from(a) ^ u32::from(b) == 1 << (IPV4_BITS - cidr)
+}
+
+/**
+ * Returns the index (0 to 3) of the interesting octet, the octet of the mask holding the last network bit <br>
+ * e.g. 3 (the 4th octet) for a /26 and 2 (the 3rd octet) for a /20 or a /24
+ */
+pub fn interesting_octet(cidr: u32) -> usize {
+    (cidr.max(1) as usize - 1) / 8
+}
+
+/// Returns the block size (or magic number) of the subnet, i.e. `256` minus the interesting octet of the mask
+pub fn block_size(cidr: u32) -> u32 {
+    1 << (8 * (interesting_octet(cidr) as u32 + 1) - cidr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_and_cidr_round_trip() {
+        for cidr in 0..=IPV4_BITS {
+            assert_eq!(mask_to_cidr(cidr_to_mask(cidr).unwrap()), Some(cidr));
+        }
+        assert_eq!(cidr_to_mask(33), None);
+        assert_eq!(mask_to_cidr(Ipv4Addr::new(255, 0, 255, 0)), None);
+    }
+
+    #[test]
+    fn required_host_bits_at_boundaries() {
+        assert_eq!(required_host_bits(1), 2);
+        assert_eq!(required_host_bits(2), 2);
+        assert_eq!(required_host_bits(3), 3);
+        assert_eq!(required_host_bits(62), 6);
+        assert_eq!(required_host_bits(63), 7);
+        assert_eq!(required_host_bits(64), 7);
+        assert_eq!(required_host_bits(126), 7);
+        assert_eq!(required_host_bits(127), 8);
+        assert_eq!(required_host_bits(1000), 10);
+        assert_eq!(required_host_bits(u32::MAX - 1), 32);
+        assert_eq!(required_host_bits(u32::MAX), 33);
+    }
+
+    #[test]
+    fn prefix_and_sizes_of_hosts() {
+        assert_eq!(hosts_to_prefix(50), Some(26));
+        assert_eq!(hosts_to_prefix(u32::MAX), None);
+        assert_eq!(addresses(26), 64);
+        assert_eq!(usable_hosts(26), 62);
+        assert_eq!(usable_hosts(31), 2);
+        assert_eq!(usable_hosts(32), 1);
+    }
+
+    #[test]
+    fn sibling_networks() {
+        assert!(are_siblings(
+            Ipv4Addr::new(10, 0, 0, 0),
+            Ipv4Addr::new(10, 0, 0, 128),
+            25
+        ));
+        assert!(!are_siblings(
+            Ipv4Addr::new(10, 0, 0, 128),
+            Ipv4Addr::new(10, 0, 1, 0),
+            25
+        ));
+        assert!(!are_siblings(Ipv4Addr::UNSPECIFIED, Ipv4Addr::BROADCAST, 0));
+    }
+
+    #[test]
+    fn range_of_address() {
+        let ip = Ipv4Addr::new(192, 168, 1, 77);
+        assert_eq!(network(ip, 26), Ipv4Addr::new(192, 168, 1, 64));
+        assert_eq!(broadcast(ip, 26), Ipv4Addr::new(192, 168, 1, 127));
+        assert_eq!(network(ip, 0), Ipv4Addr::UNSPECIFIED);
+        assert_eq!(broadcast(ip, 32), ip);
+    }
+
+    #[test]
+    fn ipv6_masks_and_ranges() {
+        let ip: Ipv6Addr = "2001:db8:0:3::77".parse().unwrap();
+        assert_eq!(
+            prefix_mask::<Ipv6Addr>(64),
+            "ffff:ffff:ffff:ffff::".parse().ok()
+        );
+        assert_eq!(prefix_mask::<Ipv6Addr>(0), Some(Ipv6Addr::UNSPECIFIED));
+        assert_eq!(
+            prefix_mask::<Ipv6Addr>(128),
+            Some(Ipv6Addr::from(u128::MAX))
+        );
+        assert_eq!(prefix_mask::<Ipv6Addr>(129), None);
+        assert_eq!(
+            network_of(ip, 48),
+            "2001:db8::".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(
+            last_address(ip, 64),
+            "2001:db8:0:3:ffff:ffff:ffff:ffff"
+                .parse::<Ipv6Addr>()
+                .unwrap()
+        );
+    }
+}
@@ -1,14 +1,47 @@
-use std::fs::File;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
 use std::io::{self, Write};
+use std::path::Path;
 
-use crate::subnet::Subnet;
+use serde::Serialize;
 
-/// Struct that contains the file path of the md or csv file and the subnets array
+use crate::math::IPV4_BITS;
+use crate::subnet::{AddressScope, Subnet, SubnetError, TABLE_COLUMNS};
+use crate::subnets_calculator::plan_totals;
+
+/// Struct that contains the file path of the output file and the subnets array
 pub struct SaveToFile {
     filepath: String,
     subnets: Vec<Subnet>,
 }
 
+/// Field delimiter and line ending of the CSV files, the defaults being a comma and LF like the `csv` crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub crlf: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            crlf: false,
+        }
+    }
+}
+
+/// Subnet serialized to JSON or YAML along with its computed fields
+#[derive(Serialize)]
+struct ExportedSubnet<'a> {
+    #[serde(flatten)]
+    subnet: &'a Subnet,
+    wasted_hosts: u32,
+    address_scope: AddressScope,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    extra: &'a BTreeMap<String, String>,
+}
+
 /// Saves the subnets to a file
 impl SaveToFile {
     pub fn new(filepath: &str, subnets: Vec<Subnet>) -> SaveToFile {
@@ -18,28 +51,475 @@ impl SaveToFile {
         }
     }
 
-    /// Saves the subnets information to a file in Markdown format (table)
+    /// Saves the subnets information to a file in Markdown format (table) followed by the totals of the subnets, see [`plan_totals`](crate::subnets_calculator::plan_totals)
     pub fn save_md(&self) -> io::Result<()> {
-        let mut file = File::create(&self.filepath)?;
-        for (i, field) in self.subnets.iter().enumerate() {
-            writeln!(
-                file,
-                "## Subnet {}:\n\n{}\n{}",
-                i,
-                field.to_markdown_table(),
-                "-".repeat(3)
-            )?;
-        }
-        Ok(())
+        write_md(&self.subnets, File::create(&self.filepath)?)
     }
 
-    /// Saves the subnets information to a file in CSV format using the serde serializer
+    /**
+     * Saves the subnets information to a file in CSV format using the serde serializer <br>
+     * The header is stable: `name,network,mask,wildcard,class,cidr,first_host,last_host,broadcast,gateway,hosts,usable_hosts,total_addresses,next_network,prefix`
+     */
     pub fn save_csv(&self) -> Result<(), csv::Error> {
-        let mut wtr = csv::Writer::from_path(&self.filepath)?;
-        for field in &self.subnets {
-            wtr.serialize(field)?;
+        write_csv(&self.subnets, File::create(&self.filepath)?)
+    }
+
+    /// Saves the subnets information to a file in CSV format with the given delimiter and line ending, see [`SaveToFile::save_csv`]
+    pub fn save_csv_with(&self, options: CsvOptions) -> Result<(), csv::Error> {
+        write_csv_with(&self.subnets, File::create(&self.filepath)?, options)
+    }
+
+    /// Saves the subnets information to a file in JSON format (array of objects) using the serde serializer
+    pub fn save_json(&self) -> io::Result<()> {
+        write_json(&self.subnets, File::create(&self.filepath)?)
+    }
+
+    /// Saves the subnets information to a file in YAML format (sequence of mappings) using the serde serializer
+    pub fn save_yaml(&self) -> io::Result<()> {
+        write_yaml(&self.subnets, File::create(&self.filepath)?)
+    }
+
+    /// Saves the subnets information to a file in HTML format (styled table)
+    pub fn save_html(&self) -> io::Result<()> {
+        write_html(&self.subnets, File::create(&self.filepath)?)
+    }
+
+    /// Saves the subnets to a text file as a prefix list (one `network/cidr` per line)
+    pub fn save_prefixes(&self) -> io::Result<()> {
+        write_prefixes(&self.subnets, File::create(&self.filepath)?)
+    }
+
+    /// Saves the subnets to an ISC dhcpd configuration file (one `subnet` stanza per subnet)
+    pub fn save_dhcpd(&self) -> io::Result<()> {
+        write_dhcpd(&self.subnets, File::create(&self.filepath)?)
+    }
+
+    /**
+     * Saves the subnets information to `basename.csv`, `basename.md`, `basename.json` and `basename.html`
+     * in the directory, which is created if needed <br>
+     * Every file is attempted, the returned error lists all the files that couldn't be written
+     */
+    pub fn save_all(&self, dir: &str, basename: &str) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+
+        let save = |extension: &str| {
+            let path = Path::new(dir).join(format!("{}.{}", basename, extension));
+            let file = SaveToFile::new(&path.to_string_lossy(), self.subnets.clone());
+            let result = match extension {
+                "csv" => file.save_csv().map_err(io::Error::other),
+                "md" => file.save_md(),
+                "json" => file.save_json(),
+                _ => file.save_html(),
+            };
+            result.map_err(|e| format!("{}: {}", path.display(), e))
+        };
+
+        let errors: Vec<String> = ["csv", "md", "json", "html"]
+            .into_iter()
+            .filter_map(|extension| save(extension).err())
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(io::Error::other(errors.join("; ")))
         }
-        wtr.flush()?;
-        Ok(())
+    }
+}
+
+/// Writes the subnets information in Markdown format (table) followed by their totals, see [`SaveToFile::save_md`]
+pub fn write_md<W: Write>(subnets: &[Subnet], mut writer: W) -> io::Result<()> {
+    for (i, field) in subnets.iter().enumerate() {
+        writeln!(
+            writer,
+            "## Subnet {}:\n\n{}\n{}",
+            i,
+            field.to_markdown_table(),
+            "-".repeat(3)
+        )?;
+    }
+    writeln!(writer, "## Summary:\n\n{}", plan_totals(subnets))?;
+    Ok(())
+}
+
+/// Writes the subnets information in CSV format, see [`SaveToFile::save_csv`]
+pub fn write_csv<W: Write>(subnets: &[Subnet], writer: W) -> Result<(), csv::Error> {
+    write_csv_with(subnets, writer, CsvOptions::default())
+}
+
+/// Writes the subnets information in CSV format with the given delimiter and line ending, see [`SaveToFile::save_csv_with`]
+pub fn write_csv_with<W: Write>(
+    subnets: &[Subnet],
+    writer: W,
+    options: CsvOptions,
+) -> Result<(), csv::Error> {
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .terminator(if options.crlf {
+            csv::Terminator::CRLF
+        } else {
+            csv::Terminator::Any(b'\n')
+        })
+        .from_writer(writer);
+    for field in subnets {
+        wtr.serialize(field)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes the subnets information in JSON format (array of objects), see [`SaveToFile::save_json`]
+pub fn write_json<W: Write>(subnets: &[Subnet], writer: W) -> io::Result<()> {
+    serde_json::to_writer_pretty(writer, &exported_subnets(subnets))?;
+    Ok(())
+}
+
+/// Writes the subnets information in YAML format with the same fields as the JSON, see [`SaveToFile::save_yaml`]
+pub fn write_yaml<W: Write>(subnets: &[Subnet], writer: W) -> io::Result<()> {
+    serde_yaml::to_writer(writer, &exported_subnets(subnets)).map_err(io::Error::other)
+}
+
+/**
+ * Writes the subnets information in HTML format, see [`SaveToFile::save_html`] <br>
+ * It's a single table with the same columns as [`Subnet::to_markdown_table`] and one row per subnet
+ */
+pub fn write_html<W: Write>(subnets: &[Subnet], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "<style>")?;
+    writeln!(
+        writer,
+        "table.subnets {{ border-collapse: collapse; font-family: monospace; }}"
+    )?;
+    writeln!(
+        writer,
+        "table.subnets th, table.subnets td {{ border: 1px solid #ccc; padding: 4px 8px; }}"
+    )?;
+    writeln!(writer, "table.subnets th {{ background: #f0f0f0; }}")?;
+    writeln!(writer, "</style>")?;
+    writeln!(writer, "<table class=\"subnets\">")?;
+
+    writeln!(writer, "  <thead>\n    <tr>")?;
+    for column in TABLE_COLUMNS {
+        writeln!(writer, "      <th>{}</th>", escape_html(column))?;
+    }
+    writeln!(writer, "    </tr>\n  </thead>")?;
+
+    writeln!(writer, "  <tbody>")?;
+    for subnet in subnets {
+        writeln!(writer, "    <tr>")?;
+        for value in subnet.table_row() {
+            writeln!(writer, "      <td>{}</td>", escape_html(&value))?;
+        }
+        writeln!(writer, "    </tr>")?;
+    }
+    writeln!(writer, "  </tbody>\n</table>")?;
+    Ok(())
+}
+
+/**
+ * Writes the subnets as a prefix list, one `network/cidr` per line (e.g. `10.0.0.0/26`),
+ * see [`SaveToFile::save_prefixes`] <br>
+ * It's the format of the IPAM bulk imports (e.g. NetBox)
+ */
+pub fn write_prefixes<W: Write>(subnets: &[Subnet], mut writer: W) -> io::Result<()> {
+    for subnet in subnets {
+        writeln!(writer, "{}/{}", subnet.network, subnet.next_cidr)?;
+    }
+    Ok(())
+}
+
+/**
+ * Writes the subnets as ISC dhcpd `subnet` stanzas, see [`SaveToFile::save_dhcpd`] <br>
+ * The pool is the usable range without the gateway, see [`Subnet::dhcp_range`], and the gateway is the `option routers` <br>
+ * Point-to-point subnets (/31 and /32) get an empty stanza, since their addresses are assigned statically
+ */
+pub fn write_dhcpd<W: Write>(subnets: &[Subnet], mut writer: W) -> io::Result<()> {
+    for subnet in subnets {
+        if let Some(name) = &subnet.name {
+            writeln!(writer, "# {}", name)?;
+        }
+        writeln!(
+            writer,
+            "subnet {} netmask {} {{",
+            subnet.network, !subnet.wildcard
+        )?;
+        if subnet.next_cidr < IPV4_BITS - 1 {
+            let (first, last) = subnet.dhcp_range();
+            writeln!(writer, "  range {} {};", first, last)?;
+            writeln!(writer, "  option routers {};", subnet.gateway)?;
+        }
+        writeln!(writer, "}}")?;
+    }
+    Ok(())
+}
+
+/**
+ * Writes each subnet rendered with the template on its own line, see [`Subnet::render`] <br>
+ * The template is checked on the first subnet, so nothing is written if it's invalid
+ */
+pub fn write_template<W: Write>(
+    subnets: &[Subnet],
+    template: &str,
+    mut writer: W,
+) -> Result<(), SubnetError> {
+    let lines = subnets
+        .iter()
+        .map(|subnet| subnet.render(template))
+        .collect::<Result<Vec<_>, _>>()?;
+    for line in lines {
+        writeln!(writer, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Helper function to escape the characters with a special meaning in HTML
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Helper function to add the computed fields to the subnets for the JSON and YAML serialization
+fn exported_subnets(subnets: &[Subnet]) -> Vec<ExportedSubnet<'_>> {
+    subnets
+        .iter()
+        .map(|subnet| ExportedSubnet {
+            subnet,
+            wasted_hosts: subnet.wasted_hosts(),
+            address_scope: subnet.address_scope(),
+            extra: &subnet.extra,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subnets_calculator::SubnetCalculator;
+
+    #[test]
+    fn json_file_matches_json_value() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator.extra = vec![BTreeMap::from([("vlan".to_string(), "10".to_string())])];
+        calculator.calculate("192.168.1.0", 24).unwrap();
+        assert_eq!(calculator.subnets[0].to_json_value()["extra"]["vlan"], "10");
+        assert!(calculator.subnets[1].to_json_value().get("extra").is_none());
+
+        let mut json = Vec::new();
+        write_json(&calculator.subnets, &mut json).unwrap();
+        let written: serde_json::Value = serde_json::from_slice(&json).unwrap();
+
+        let expected: Vec<serde_json::Value> = calculator
+            .subnets
+            .iter()
+            .map(Subnet::to_json_value)
+            .collect();
+        assert_eq!(written, serde_json::Value::from(expected));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let path = std::env::temp_dir().join("subnetting_json_round_trip.json");
+        let save = SaveToFile::new(path.to_str().unwrap(), calculator.subnets.clone());
+        save.save_json().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(contents.contains("\"wasted_hosts\""));
+
+        let subnets: Vec<Subnet> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(
+            format!("{:?}", subnets),
+            format!("{:?}", calculator.subnets)
+        );
+    }
+
+    #[test]
+    fn yaml_round_trip() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let path = std::env::temp_dir().join("subnetting_yaml_round_trip.yaml");
+        let save = SaveToFile::new(path.to_str().unwrap(), calculator.subnets.clone());
+        save.save_yaml().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(contents.contains("wasted_hosts: 14"));
+
+        let subnets: Vec<Subnet> = serde_yaml::from_str(&contents).unwrap();
+        assert_eq!(
+            format!("{:?}", subnets),
+            format!("{:?}", calculator.subnets)
+        );
+    }
+
+    #[test]
+    fn json_written_to_writer_parses_back() {
+        let mut calculator = SubnetCalculator::new(vec![100, 10]);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+
+        let mut output = Vec::new();
+        write_json(&calculator.subnets, &mut output).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        let subnets = value.as_array().unwrap();
+        assert_eq!(subnets.len(), 2);
+        assert_eq!(subnets[0]["network"], "10.0.0.0");
+        assert_eq!(subnets[1]["network"], "10.0.0.128");
+        assert_eq!(subnets[0]["wasted_hosts"], 28);
+    }
+
+    #[test]
+    fn html_has_one_cell_per_field_per_subnet() {
+        let mut calculator = SubnetCalculator::new(vec![100, 10]);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+
+        let mut output = Vec::new();
+        write_html(&calculator.subnets, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("<table"));
+        assert!(output.contains("<thead>") && output.contains("<tbody>"));
+        assert_eq!(output.matches("<th>").count(), TABLE_COLUMNS.len());
+        assert_eq!(
+            output.matches("<td>").count(),
+            TABLE_COLUMNS.len() * calculator.subnets.len()
+        );
+        assert!(output.contains("<td>10.0.0.128</td>"));
+    }
+
+    #[test]
+    fn html_values_are_escaped() {
+        assert_eq!(escape_html("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn prefix_list_has_one_prefix_per_line() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20, 2]);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+
+        let mut output = Vec::new();
+        write_prefixes(&calculator.subnets, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "10.0.0.0/26\n10.0.0.64/27\n10.0.0.96/30\n"
+        );
+    }
+
+    #[test]
+    fn save_all_writes_every_format() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let dir = std::env::temp_dir().join("subnetting_save_all");
+        let save = SaveToFile::new("", calculator.subnets.clone());
+        save.save_all(dir.to_str().unwrap(), "plan").unwrap();
+
+        for extension in ["csv", "md", "json", "html"] {
+            let path = dir.join(format!("plan.{}", extension));
+            assert!(!std::fs::read_to_string(&path).unwrap().is_empty());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn csv_written_to_writer_has_header_and_rows() {
+        let mut calculator = SubnetCalculator::new(vec![100, 10]);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+
+        let mut output = Vec::new();
+        write_csv(&calculator.subnets, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 3);
+        assert!(output.starts_with("name,network,"));
+    }
+
+    #[test]
+    fn csv_with_semicolon_and_crlf() {
+        let mut calculator = SubnetCalculator::new(vec![100, 10]);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+
+        let options = CsvOptions {
+            delimiter: b';',
+            crlf: true,
+        };
+        let mut output = Vec::new();
+        write_csv_with(&calculator.subnets, &mut output, options).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("name;network;mask;"));
+        assert!(!output.contains(','));
+        assert_eq!(output.matches("\r\n").count(), 3);
+    }
+
+    #[test]
+    fn markdown_ends_with_totals() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let mut output = Vec::new();
+        write_md(&calculator.subnets, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with(
+            "## Summary:\n\nCreated 2 subnets; 92 usable hosts across 96 allocated addresses.\n"
+        ));
+    }
+
+    #[test]
+    fn dhcpd_stanza_of_slash_26() {
+        let mut calculator = SubnetCalculator::new(vec![50]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let mut output = Vec::new();
+        write_dhcpd(&calculator.subnets, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "subnet 192.168.1.0 netmask 255.255.255.192 {\n  range 192.168.1.1 192.168.1.61;\n  option routers 192.168.1.62;\n}\n"
+        );
+    }
+
+    #[test]
+    fn csv_header_is_stable() {
+        let mut calculator = SubnetCalculator::new(vec![50]);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+        let path = std::env::temp_dir().join("subnetting_csv_header_is_stable.csv");
+        let save = SaveToFile::new(path.to_str().unwrap(), calculator.subnets.clone());
+        save.save_csv().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            contents.lines().next().unwrap(),
+            "name,network,mask,wildcard,class,cidr,first_host,last_host,broadcast,gateway,hosts,usable_hosts,total_addresses,next_network,prefix"
+        );
+    }
+
+    #[test]
+    fn table_columns_match_json_keys() {
+        let mut calculator = SubnetCalculator::new(vec![50]);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+
+        let mut json = Vec::new();
+        write_json(&calculator.subnets, &mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        let keys: Vec<_> = json
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix('"'))
+            .filter_map(|line| line.split_once("\":").map(|(key, _)| key))
+            .collect();
+        let columns: Vec<_> = TABLE_COLUMNS
+            .iter()
+            .map(|column| column.to_lowercase().replace(' ', "_"))
+            .collect();
+        assert_eq!(keys, columns);
     }
 }
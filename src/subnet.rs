@@ -1,35 +1,611 @@
 use std::{
+    collections::BTreeMap,
     fmt::Display,
     io,
     net::Ipv4Addr,
+    num::ParseIntError,
     ops::{BitAnd, BitOr},
+    str::FromStr,
 };
 
-use ipnet::IpAdd;
+use ipnet::{IpAdd, IpSub};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-/// The number of bits in an IPv4 address
-const IPV4_BITS: u32 = 32;
+use crate::math::{self, IPV4_BITS, MIN_HOST_BITS};
+
 /// The maximum value of an octet in an IPv4 address
 const MAX_OCTET_VALUE: u8 = 255;
 
+/**
+ * The columns of the Markdown and HTML tables, see [`Subnet::to_markdown_table`] <br>
+ * They're in the same order as the serialized fields of a [`Subnet`], followed by the computed fields of the JSON and YAML files
+ */
+pub(crate) const TABLE_COLUMNS: [&str; 17] = [
+    "Name",
+    "Network",
+    "Mask",
+    "Wildcard",
+    "Class",
+    "CIDR",
+    "First Host",
+    "Last Host",
+    "Broadcast",
+    "Gateway",
+    "Hosts",
+    "Usable Hosts",
+    "Total Addresses",
+    "Next Network",
+    "Prefix",
+    "Wasted Hosts",
+    "Address Scope",
+];
+
 #[derive(Debug, Error)]
 /// Error type for the Subnet
 pub enum SubnetError {
     #[error("Invalid IP address: {0}")]
     InvalidIpAddress(String),
+    #[error("Incomplete IP address: {address} has {octets} octets, expected 4 (e.g. 192.168.1.0 instead of 192.168.1)")]
+    IncompleteIpAddress { address: String, octets: usize },
+    #[error("Ambiguous IP address: the octet {octet} of {address} has a leading zero and could be read as octal, write it without the zeros")]
+    AmbiguousOctet { address: String, octet: String },
     #[error("Invalid CIDR: {0}")]
     InvalidCidr(u32),
+    #[error("Invalid CIDR: {0} is not a number")]
+    InvalidCidrFormat(String),
+    #[error("Missing CIDR: {0} (expected an address like 192.168.1.0/24)")]
+    MissingCidr(String),
+    #[error("{address} is not a network address, did you mean {network}/{cidr}?")]
+    NotANetworkAddress {
+        address: Ipv4Addr,
+        network: Ipv4Addr,
+        cidr: u32,
+    },
+    #[error("The number of hosts must be greater than 0")]
+    ZeroHosts,
+    #[error("The number of subnets must be greater than 0")]
+    ZeroSubnets,
+    #[error("Invalid number of hosts: {0}")]
+    InvalidHosts(String),
+    #[error("Invalid gateway policy: {0} (expected first or last)")]
+    InvalidGatewayPolicy(String),
+    #[error("Invalid sort order: {0} (expected input, network or size)")]
+    InvalidSortOrder(String),
+    #[error("Invalid template: {0} (expected a subnet field like {{network}} and braces escaped as {{{{ and }}}})")]
+    InvalidTemplate(String),
+    #[error("Invalid mask: {0} (the ones must be followed only by zeros, e.g. 255.255.240.0)")]
+    InvalidMask(Ipv4Addr),
+    #[error("Invalid packing strategy: {0} (expected largest-first, smallest-first or best-fit)")]
+    InvalidPackingStrategy(String),
+    #[error("Invalid number: {0}")]
+    InvalidNumber(#[from] ParseIntError),
+    #[error("Invalid choice: {0}")]
+    InvalidChoice(String),
+    #[error("Invalid import: {0}")]
+    InvalidImport(String),
+    #[error("Invalid CSV: {0}")]
+    CsvError(#[from] csv::Error),
+    #[error("Invalid JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Invalid fill direction: {0} (expected bottom-up or top-down)")]
+    InvalidFillDirection(String),
+    #[error("Invalid verbosity: {0} (expected compact, normal or detailed)")]
+    InvalidVerbosity(String),
+    #[error("Invalid plan: {discrepancies} discrepancies found")]
+    InvalidPlan { discrepancies: usize },
+    #[error("Too many hosts: {hosts} hosts don't fit in a /{cidr} network")]
+    TooManyHosts { hosts: u32, cidr: u32 },
+    #[error(
+        "Insufficient space: requested {requested} addresses but only {available} are available{}",
+        first_overflow_note(.first_overflow)
+    )]
+    InsufficientSpace {
+        requested: u64,
+        available: u64,
+        /// Index in the hosts array of the first subnet that doesn't fit, if it's known
+        first_overflow: Option<usize>,
+    },
+    #[error("Too many subnets: requested {requested} but only {available} fit")]
+    TooManySubnets { requested: u64, available: u64 },
+    #[error("Invalid host range: the {role} {address} isn't a host of {network}/{prefix}")]
+    HostOutsideSubnet {
+        role: &'static str,
+        address: Ipv4Addr,
+        network: Ipv4Addr,
+        prefix: u32,
+    },
+    #[error("Unaligned start address: {address} isn't a network address of a /{prefix}, the size of the largest subnet")]
+    UnalignedStart { address: Ipv4Addr, prefix: u32 },
+    #[error(
+        "Unsupported streaming: the subnets are streamed largest-first and bottom-up, not {0}"
+    )]
+    UnsupportedStreaming(String),
+    #[error("Too many subnets in the imported file: more than the limit of {max}")]
+    TooManyImportedSubnets { max: usize },
+    #[error("Address space exhausted: the subnets don't fit before 255.255.255.255")]
+    AddressSpaceExhausted,
     #[error("IO error: {0}")]
     IoError(#[from] io::Error),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
-/// Struct that contains the subnet information and calculated fields
+/// Helper function to name the first subnet that doesn't fit in the message of [`SubnetError::InsufficientSpace`]
+fn first_overflow_note(first_overflow: &Option<usize>) -> String {
+    first_overflow.map_or(String::new(), |index| {
+        format!(
+            ", subnet #{} of the input is the first that doesn't fit",
+            index + 1
+        )
+    })
+}
+
+/// Machine-readable details of the errors, used by the CLI to report them to scripts
+impl SubnetError {
+    /// Returns the name of the variant (e.g. `InvalidCidr`)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SubnetError::InvalidIpAddress(_) => "InvalidIpAddress",
+            SubnetError::IncompleteIpAddress { .. } => "IncompleteIpAddress",
+            SubnetError::AmbiguousOctet { .. } => "AmbiguousOctet",
+            SubnetError::InvalidCidr(_) => "InvalidCidr",
+            SubnetError::InvalidCidrFormat(_) => "InvalidCidrFormat",
+            SubnetError::MissingCidr(_) => "MissingCidr",
+            SubnetError::NotANetworkAddress { .. } => "NotANetworkAddress",
+            SubnetError::ZeroHosts => "ZeroHosts",
+            SubnetError::ZeroSubnets => "ZeroSubnets",
+            SubnetError::InvalidHosts(_) => "InvalidHosts",
+            SubnetError::InvalidGatewayPolicy(_) => "InvalidGatewayPolicy",
+            SubnetError::InvalidSortOrder(_) => "InvalidSortOrder",
+            SubnetError::InvalidTemplate(_) => "InvalidTemplate",
+            SubnetError::InvalidMask(_) => "InvalidMask",
+            SubnetError::InvalidPackingStrategy(_) => "InvalidPackingStrategy",
+            SubnetError::InvalidFillDirection(_) => "InvalidFillDirection",
+            SubnetError::InvalidVerbosity(_) => "InvalidVerbosity",
+            SubnetError::InvalidPlan { .. } => "InvalidPlan",
+            SubnetError::UnalignedStart { .. } => "UnalignedStart",
+            SubnetError::UnsupportedStreaming(_) => "UnsupportedStreaming",
+            SubnetError::InvalidNumber(_) => "InvalidNumber",
+            SubnetError::InvalidChoice(_) => "InvalidChoice",
+            SubnetError::InvalidImport(_) => "InvalidImport",
+            SubnetError::CsvError(_) => "CsvError",
+            SubnetError::JsonError(_) => "JsonError",
+            SubnetError::TooManyHosts { .. } => "TooManyHosts",
+            SubnetError::InsufficientSpace { .. } => "InsufficientSpace",
+            SubnetError::TooManySubnets { .. } => "TooManySubnets",
+            SubnetError::HostOutsideSubnet { .. } => "HostOutsideSubnet",
+            SubnetError::TooManyImportedSubnets { .. } => "TooManyImportedSubnets",
+            SubnetError::AddressSpaceExhausted => "AddressSpaceExhausted",
+            SubnetError::IoError(_) => "IoError",
+        }
+    }
+
+    /// Returns the offending value for the variants that have one (e.g. `33` for `InvalidCidr(33)`), otherwise the message
+    pub fn detail(&self) -> String {
+        match self {
+            SubnetError::InvalidIpAddress(value)
+            | SubnetError::InvalidCidrFormat(value)
+            | SubnetError::MissingCidr(value)
+            | SubnetError::InvalidHosts(value)
+            | SubnetError::InvalidGatewayPolicy(value)
+            | SubnetError::InvalidSortOrder(value)
+            | SubnetError::InvalidTemplate(value)
+            | SubnetError::InvalidPackingStrategy(value)
+            | SubnetError::InvalidFillDirection(value)
+            | SubnetError::InvalidVerbosity(value)
+            | SubnetError::InvalidChoice(value)
+            | SubnetError::UnsupportedStreaming(value)
+            | SubnetError::InvalidImport(value) => value.clone(),
+            SubnetError::IncompleteIpAddress { address, .. } => address.clone(),
+            SubnetError::AmbiguousOctet { address, .. } => address.clone(),
+            SubnetError::InvalidCidr(cidr) => cidr.to_string(),
+            SubnetError::InvalidMask(mask) => mask.to_string(),
+            SubnetError::UnalignedStart { address, .. } => address.to_string(),
+            SubnetError::InvalidNumber(e) => e.to_string(),
+            SubnetError::CsvError(e) => e.to_string(),
+            SubnetError::JsonError(e) => e.to_string(),
+            SubnetError::IoError(e) => e.to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /**
+     * Returns the exit code of the CLI for the error <br>
+     * The codes are stable: 10-19 and 40-59 are invalid inputs (including malformed CSV and JSON files), 20-29 are allocation failures and 30 is an IO error
+     */
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            SubnetError::InvalidIpAddress(_) => 10,
+            SubnetError::InvalidCidr(_) => 11,
+            SubnetError::NotANetworkAddress { .. } => 12,
+            SubnetError::ZeroHosts => 13,
+            SubnetError::InvalidHosts(_) => 14,
+            SubnetError::InvalidGatewayPolicy(_) => 15,
+            SubnetError::InvalidSortOrder(_) => 16,
+            SubnetError::InvalidCidrFormat(_) => 17,
+            SubnetError::MissingCidr(_) => 18,
+            SubnetError::InvalidTemplate(_) => 19,
+            SubnetError::InvalidMask(_) => 40,
+            SubnetError::InvalidPackingStrategy(_) => 41,
+            SubnetError::InvalidNumber(_) => 42,
+            SubnetError::InvalidChoice(_) => 43,
+            SubnetError::InvalidImport(_) => 44,
+            SubnetError::CsvError(_) => 45,
+            SubnetError::JsonError(_) => 46,
+            SubnetError::IncompleteIpAddress { .. } => 47,
+            SubnetError::AmbiguousOctet { .. } => 48,
+            SubnetError::InvalidFillDirection(_) => 49,
+            SubnetError::InvalidVerbosity(_) => 50,
+            SubnetError::InvalidPlan { .. } => 51,
+            SubnetError::UnalignedStart { .. } => 52,
+            SubnetError::ZeroSubnets => 53,
+            SubnetError::UnsupportedStreaming(_) => 54,
+            SubnetError::TooManyHosts { .. } => 20,
+            SubnetError::InsufficientSpace { .. } => 21,
+            SubnetError::TooManySubnets { .. } => 22,
+            SubnetError::AddressSpaceExhausted => 23,
+            SubnetError::HostOutsideSubnet { .. } => 24,
+            SubnetError::TooManyImportedSubnets { .. } => 25,
+            SubnetError::IoError(_) => 30,
+        }
+    }
+
+    /// Returns the error as a JSON object (e.g. `{"error":"InvalidCidr","detail":"33"}`)
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "error": self.kind(), "detail": self.detail() }).to_string()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+/// Position of the gateway among the usable hosts of a subnet
+pub enum GatewayPolicy {
+    /// The first usable address is the gateway
+    First,
+    /// The last usable address is the gateway
+    #[default]
+    Last,
+}
+
+/// Parses a gateway policy from `first` or `last` (case insensitive)
+impl FromStr for GatewayPolicy {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "first" => Ok(GatewayPolicy::First),
+            "last" => Ok(GatewayPolicy::Last),
+            _ => Err(SubnetError::InvalidGatewayPolicy(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Syntax of the access list lines of [`Subnet::acl_line`]
+pub enum AclStyle {
+    /// Cisco IOS standard syntax with the wildcard mask, e.g. `access-list permit ip 10.1.1.0 0.0.0.255 any`
+    #[default]
+    CiscoIos,
+    /// Vendor-neutral syntax with the prefix length, e.g. `permit ip 10.1.1.0/24 any`
+    Generic,
+}
+
+impl AclStyle {
+    /// Returns the template of the line, see [`Subnet::render`]
+    pub fn template(&self) -> &'static str {
+        match self {
+            AclStyle::CiscoIos => "access-list permit ip {network} {wildcard} any",
+            AclStyle::Generic => "permit ip {network}/{prefix} any",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Amount of detail of the printed subnet information, see [`Subnet::format_with`]
+pub enum Verbosity {
+    /// One line per subnet, see [`Subnet::summary`]
+    Compact,
+    /// The subnet information of the [`Display`] implementation
+    #[default]
+    Normal,
+    /// Also the network and the mask in binary, the block size, the interesting octet, the borrowed bits
+    /// and the addresses as integers, i.e. the alternate [`Display`] (`{:#}`)
+    Detailed,
+}
+
+/// Parses a verbosity from `compact`, `normal` or `detailed` (case insensitive)
+impl FromStr for Verbosity {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "compact" => Ok(Verbosity::Compact),
+            "normal" => Ok(Verbosity::Normal),
+            "detailed" => Ok(Verbosity::Detailed),
+            _ => Err(SubnetError::InvalidVerbosity(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+/// Scope of an IPv4 address range, see [`Subnet::address_scope`]
+pub enum AddressScope {
+    /// 0.0.0.0/8, "this network"
+    Unspecified,
+    /// 10.0.0.0/8, 172.16.0.0/12 and 192.168.0.0/16 (RFC 1918)
+    Private,
+    /// 100.64.0.0/10, carrier-grade NAT (RFC 6598)
+    Shared,
+    /// 127.0.0.0/8
+    Loopback,
+    /// 169.254.0.0/16 (RFC 3927)
+    LinkLocal,
+    /// 192.0.2.0/24, 198.51.100.0/24 and 203.0.113.0/24 (RFC 5737)
+    Documentation,
+    /// 224.0.0.0/4
+    Multicast,
+    /// 240.0.0.0/4, including the limited broadcast address
+    Reserved,
+    /// Any other address
+    Public,
+}
+
+impl AddressScope {
+    /// Returns the scope of the address
+    pub fn of(ip: Ipv4Addr) -> AddressScope {
+        let octets = ip.octets();
+        if octets[0] == 0 {
+            AddressScope::Unspecified
+        } else if ip.is_private() {
+            AddressScope::Private
+        } else if octets[0] == 100 && octets[1] & 0b1100_0000 == 64 {
+            AddressScope::Shared
+        } else if ip.is_loopback() {
+            AddressScope::Loopback
+        } else if ip.is_link_local() {
+            AddressScope::LinkLocal
+        } else if ip.is_documentation() {
+            AddressScope::Documentation
+        } else if ip.is_multicast() {
+            AddressScope::Multicast
+        } else if octets[0] >= 240 {
+            AddressScope::Reserved
+        } else {
+            AddressScope::Public
+        }
+    }
+}
+
+/// Prints the scope in a human-readable format (e.g. `Link-local`)
+impl Display for AddressScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AddressScope::Unspecified => "Unspecified",
+            AddressScope::Private => "Private",
+            AddressScope::Shared => "Shared",
+            AddressScope::Loopback => "Loopback",
+            AddressScope::LinkLocal => "Link-local",
+            AddressScope::Documentation => "Documentation",
+            AddressScope::Multicast => "Multicast",
+            AddressScope::Reserved => "Reserved",
+            AddressScope::Public => "Public",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// IPv4 network address with its CIDR, parsed from the CIDR notation (e.g. `192.168.1.0/24`) or with a mask
+pub struct NetworkSpec {
+    pub network: Ipv4Addr,
+    pub cidr: u32,
+}
+
+/**
+ * Parses a network in CIDR notation, or with a mask after a slash or a space (e.g. `192.168.1.0 255.255.255.0`) <br>
+ * Returns [`SubnetError::MissingCidr`] without a slash or a mask, [`SubnetError::InvalidIpAddress`] for a bad address,
+ * [`SubnetError::InvalidCidrFormat`] for a non-numeric CIDR, [`SubnetError::InvalidCidr`] for a CIDR above 32
+ * and [`SubnetError::InvalidMask`] for a mask whose ones aren't contiguous
+ */
+impl FromStr for NetworkSpec {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let (network, cidr) = trimmed
+            .split_once('/')
+            .or_else(|| trimmed.split_once(char::is_whitespace))
+            .ok_or_else(|| SubnetError::MissingCidr(s.to_string()))?;
+        let network = Subnet::string_to_ip(network.trim())?;
+        let cidr = cidr.trim();
+        if cidr.contains('.') {
+            let mask = Subnet::string_to_ip(cidr)?;
+            let cidr = Subnet::mask_to_cidr(mask)?;
+            return Ok(NetworkSpec { network, cidr });
+        }
+        let cidr = cidr
+            .parse()
+            .map_err(|_| SubnetError::InvalidCidrFormat(cidr.to_string()))?;
+        if cidr > IPV4_BITS {
+            return Err(SubnetError::InvalidCidr(cidr));
+        }
+
+        Ok(NetworkSpec { network, cidr })
+    }
+}
+
+impl NetworkSpec {
+    /**
+     * Parses a network like [`NetworkSpec::from_str`], but a bare address without a CIDR or a mask (e.g. `10.0.0.0`)
+     * gets the prefix of the classful default mask of its class: /8 for class A, /16 for class B and /24 for class C <br>
+     * It's the notation of old-school inputs, the addresses of the classes D and E still return [`SubnetError::MissingCidr`]
+     */
+    pub fn parse_classful(s: &str) -> Result<NetworkSpec, SubnetError> {
+        match s.parse() {
+            Err(SubnetError::MissingCidr(_)) => {
+                let network = Subnet::string_to_ip(s.trim())?;
+                let cidr = math::default_prefix(math::class_of(network))
+                    .ok_or_else(|| SubnetError::MissingCidr(s.to_string()))?;
+                Ok(NetworkSpec { network, cidr })
+            }
+            result => result,
+        }
+    }
+}
+
+/// Prints the network in CIDR notation (e.g. `192.168.1.0/24`)
+impl Display for NetworkSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.network, self.cidr)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Size of a requested subnet, either a number of hosts or a fixed prefix length
+pub enum HostRequirement {
+    /// Number of hosts (e.g. `50`)
+    Hosts(u32),
+    /// Prefix length (e.g. `/28`), the subnet has `2^(32 - prefix) - 2` usable hosts
+    Prefix(u32),
+    /// Number of hosts with a growth headroom (e.g. `50+30%` or `50*1.3`), the subnet is sized for the grown hosts
+    Growth {
+        /// Requested number of hosts, before the growth
+        hosts: u32,
+        /// Number of hosts after the growth, rounded up
+        grown: u32,
+    },
+}
+
+impl HostRequirement {
+    /// Returns the number of hosts of the requirement
+    pub fn hosts(self) -> u32 {
+        match self {
+            HostRequirement::Hosts(hosts) => hosts,
+            HostRequirement::Prefix(prefix) => ((1u64 << (IPV4_BITS - prefix)) - 2) as u32,
+            HostRequirement::Growth { grown, .. } => grown,
+        }
+    }
+
+    /// Returns the prefix length if the requirement is a prefix
+    pub fn prefix(self) -> Option<u32> {
+        match self {
+            HostRequirement::Prefix(prefix) => Some(prefix),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of hosts before the growth if the requirement has a growth headroom
+    pub fn base_hosts(self) -> Option<u32> {
+        match self {
+            HostRequirement::Growth { hosts, .. } => Some(hosts),
+            _ => None,
+        }
+    }
+
+    /**
+     * Helper function to parse a number of hosts with a growth headroom, either a percentage (e.g. `50+30%`)
+     * or a factor of at least 1 (e.g. `50*1.3`) <br>
+     * It returns None if there's no headroom, the grown hosts are rounded up (e.g. 65 for `50+30%`)
+     */
+    fn parse_growth(s: &str) -> Option<Result<HostRequirement, SubnetError>> {
+        let invalid = || SubnetError::InvalidHosts(s.to_string());
+
+        // The growth is kept as a fraction to avoid the rounding errors of the floats (e.g. 50 * 1.3 = 65.00000000000001)
+        let (hosts, numerator, denominator) = if let Some((hosts, percent)) = s.split_once('+') {
+            let percent = percent.trim().strip_suffix('%')?;
+            let (numerator, denominator) = match parse_decimal(percent) {
+                Some(fraction) => fraction,
+                None => return Some(Err(invalid())),
+            };
+            (hosts, 100 * denominator + numerator, 100 * denominator)
+        } else if let Some((hosts, factor)) = s.split_once('*') {
+            match parse_decimal(factor) {
+                Some((numerator, denominator)) if numerator >= denominator => {
+                    (hosts, numerator, denominator)
+                }
+                _ => return Some(Err(invalid())),
+            }
+        } else {
+            return None;
+        };
+
+        let hosts = match Subnet::parse_hosts(hosts) {
+            Ok(hosts) => hosts,
+            Err(e) => return Some(Err(e)),
+        };
+        let grown = (u128::from(hosts) * numerator).div_ceil(denominator);
+        Some(
+            u32::try_from(grown)
+                .map(|grown| HostRequirement::Growth { hosts, grown })
+                .map_err(|_| invalid()),
+        )
+    }
+}
+
+/// Helper function to parse a non-negative decimal number (e.g. `1.3`) as a fraction (e.g. `13/10`)
+fn parse_decimal(s: &str) -> Option<(u128, u128)> {
+    let s = s.trim();
+    let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if integer.len() + fraction.len() == 0
+        || fraction.len() > 9
+        || !is_digits(integer)
+        || !is_digits(fraction)
+    {
+        return None;
+    }
+
+    let denominator = 10u128.pow(fraction.len() as u32);
+    let integer: u128 = if integer.is_empty() {
+        0
+    } else {
+        integer.parse::<u64>().ok()?.into()
+    };
+    let fraction: u128 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse().ok()?
+    };
+    Some((integer * denominator + fraction, denominator))
+}
+
+/**
+ * Parses a host requirement from a number of hosts (e.g. `50`), a prefix length (e.g. `/28`)
+ * or a number of hosts with a growth headroom (e.g. `50+30%` or `50*1.3`) <br>
+ * Prefixes longer than /30 are rejected as they have no usable hosts, use [`Subnet::point_to_point`] instead
+ */
+impl FromStr for HostRequirement {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().strip_prefix('/') {
+            Some(prefix) => {
+                let prefix = prefix
+                    .trim()
+                    .parse()
+                    .map_err(|_| SubnetError::InvalidHosts(s.to_string()))?;
+                if prefix > IPV4_BITS - MIN_HOST_BITS {
+                    return Err(SubnetError::InvalidCidr(prefix));
+                }
+                Ok(HostRequirement::Prefix(prefix))
+            }
+            None => HostRequirement::parse_growth(s)
+                .unwrap_or_else(|| Subnet::parse_hosts(s).map(HostRequirement::Hosts)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/**
+ * Struct that contains the subnet information and calculated fields <br>
+ * The fields are serialized in declaration order, which is the stable column order of the CSV files
+ * and key order of the JSON and YAML files: new fields go at the end and existing ones are never reordered
+ */
 pub struct Subnet {
+    /// Label of the subnet (e.g. `Sales`), `None` if the subnet isn't named
+    #[serde(default)]
+    pub name: Option<String>,
     pub network: Ipv4Addr,
     pub mask: Ipv4Addr,
+    pub wildcard: Ipv4Addr,
     pub class: char,
     pub cidr: u32,
     pub first_host: Ipv4Addr,
@@ -37,20 +613,145 @@ pub struct Subnet {
     pub broadcast: Ipv4Addr,
     pub gateway: Ipv4Addr,
     pub hosts: u32,
+    /// Number of usable hosts of the subnet, serialized as `usable_hosts`
+    #[serde(rename = "usable_hosts", alias = "real_hosts")]
     pub real_hosts: u32,
-    pub next_subnet: Ipv4Addr,
+    /// Number of addresses of the subnet including the network and broadcast addresses, i.e. `2^(32 - cidr)`
+    pub total_addresses: u64,
+    /// The network address after the broadcast, `None` if the subnet ends at 255.255.255.255, serialized as `next_network`
+    #[serde(rename = "next_network", alias = "next_subnet")]
+    pub next_subnet: Option<Ipv4Addr>,
+    /// Prefix length of the subnet, serialized as `prefix`
+    #[serde(rename = "prefix", alias = "next_cidr")]
     pub next_cidr: u32,
+    /// Allocates a /31 (RFC 3021) for 2 hosts and a /32 for a single host instead of a /30
+    #[serde(skip)]
+    pub point_to_point: bool,
+    /// Whether the gateway is the first or the last usable address
+    #[serde(skip)]
+    pub gateway_policy: GatewayPolicy,
+    /// The prefix length if the subnet was requested by prefix (e.g. /28) instead of number of hosts
+    #[serde(skip)]
+    pub requested_prefix: Option<u32>,
+    /// The number of hosts before the growth headroom, if any, [`Subnet::hosts`] being the grown hosts
+    #[serde(skip)]
+    pub requested_hosts: Option<u32>,
+    /**
+     * Tags of the subnet for the IPAM tools (e.g. `vlan`, `site` or `role`), passed through from a JSON import <br>
+     * They're written to the JSON and YAML files, but not to the CSV and Markdown files
+     */
+    #[serde(default, skip_serializing)]
+    pub extra: BTreeMap<String, String>,
+}
+
+/**
+ * Two subnets are equal if they have the same network and prefix length ([`Subnet::next_cidr`]),
+ * i.e. they're the same block of addresses, whatever hosts they were requested for
+ */
+impl PartialEq for Subnet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Subnet {}
+
+impl PartialOrd for Subnet {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders the subnets by ascending network address, then from the largest to the smallest block
+impl Ord for Subnet {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.network
+            .cmp(&other.network)
+            .then(self.next_cidr.cmp(&other.next_cidr))
+    }
+}
+
+/**
+ * Builder of a calculated [`Subnet`], the subnet is only available after [`SubnetBuilder::build`]
+ * so its calculated fields can't be read before they're set:
+ * ```compile_fail
+ * use subnetting::SubnetBuilder;
+ *
+ * let builder = SubnetBuilder::new("192.168.1.0", 24, 50);
+ * println!("{}", builder.broadcast);
+ * ```
+ * ```
+ * use subnetting::SubnetBuilder;
+ *
+ * let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+ * assert_eq!(subnet.broadcast.to_string(), "192.168.1.63");
+ * ```
+ */
+#[derive(Debug, Clone)]
+pub struct SubnetBuilder {
+    network: String,
+    cidr: u32,
+    hosts: u32,
+    point_to_point: bool,
+    gateway_policy: GatewayPolicy,
+}
+
+impl SubnetBuilder {
+    pub fn new(network: &str, cidr: u32, hosts: u32) -> SubnetBuilder {
+        SubnetBuilder {
+            network: network.to_string(),
+            cidr,
+            hosts,
+            point_to_point: false,
+            gateway_policy: GatewayPolicy::default(),
+        }
+    }
+
+    /// See [`Subnet::point_to_point`]
+    pub fn point_to_point(mut self, point_to_point: bool) -> SubnetBuilder {
+        self.point_to_point = point_to_point;
+        self
+    }
+
+    /// See [`Subnet::gateway_policy`]
+    pub fn gateway_policy(mut self, gateway_policy: GatewayPolicy) -> SubnetBuilder {
+        self.gateway_policy = gateway_policy;
+        self
+    }
+
+    /// Creates the subnet and calculates its fields, see [`Subnet::new`] and [`Subnet::calculate`]
+    pub fn build(self) -> Result<Subnet, SubnetError> {
+        let mut subnet = Subnet::new(&self.network, self.cidr, self.hosts)?;
+        subnet.point_to_point = self.point_to_point;
+        subnet.gateway_policy = self.gateway_policy;
+        subnet.calculate()?;
+        Ok(subnet)
+    }
 }
 
 /// Contains the subnet information and various methods
 impl Subnet {
+    /**
+     * Creates a subnet to be calculated with [`Subnet::calculate`] <br>
+     * The calculated fields are zeroed until then, prefer [`SubnetBuilder`] which always returns a calculated subnet <br>
+     * Returns [`SubnetError::NotANetworkAddress`] if the address has host bits set for the given CIDR (e.g. 192.168.1.37/24)
+     */
     pub fn new(network: &str, cidr: u32, hosts: u32) -> Result<Subnet, SubnetError> {
         let network = Subnet::string_to_ip(network)?;
         let mask = Subnet::cidr_to_mask(cidr)?;
 
+        if network.bitand(mask) != network {
+            return Err(SubnetError::NotANetworkAddress {
+                address: network,
+                network: network.bitand(mask),
+                cidr,
+            });
+        }
+
         Ok(Subnet {
             network,
             mask,
+            wildcard: Ipv4Addr::new(0, 0, 0, 0),
             cidr,
             broadcast: Ipv4Addr::new(0, 0, 0, 0),
             gateway: Ipv4Addr::new(0, 0, 0, 0),
@@ -58,117 +759,1844 @@ impl Subnet {
             last_host: Ipv4Addr::new(0, 0, 0, 0),
             hosts,
             real_hosts: 0,
-            class: Subnet::determine_class(cidr),
-            next_subnet: Ipv4Addr::new(0, 0, 0, 0),
+            total_addresses: 0,
+            class: math::class_of(network),
+            next_subnet: None,
             next_cidr: 0,
+            point_to_point: false,
+            gateway_policy: GatewayPolicy::default(),
+            requested_prefix: None,
+            requested_hosts: None,
+            extra: BTreeMap::new(),
+            name: None,
         })
     }
 
     /**
      * Calculates the following fields based on user input:
+     * - [`Subnet::wildcard`]
      * - [`Subnet::broadcast`]
      * - [`Subnet::gateway`]
      * - [`Subnet::first_host`]
      * - [`Subnet::last_host`]
      * - [`Subnet::real_hosts`]
+     * - [`Subnet::total_addresses`]
      * - [`Subnet::next_subnet`]
      * - [`Subnet::next_cidr`]
      * - [`Subnet::class`]
+     *
+     * If [`Subnet::point_to_point`] is set, 2 hosts are allocated to a /31 point-to-point link (RFC 3021)
+     * and a single host to a /32, where every address is usable and there's no separate broadcast or gateway <br>
+     * The gateway is placed according to [`Subnet::gateway_policy`] and excluded from the host range <br>
+     * Returns [`SubnetError::HostOutsideSubnet`] if the gateway or the host range isn't inside the subnet,
+     * see [`Subnet::check_host_range`]
      */
     pub fn calculate(&mut self) -> Result<(), SubnetError> {
-        let cidr_offset = (self.hosts.next_power_of_two() as f32).log2().ceil() as u32;
+        if self.hosts == 0 {
+            return Err(SubnetError::ZeroHosts);
+        }
 
-        let real_hosts = u32::pow(2, cidr_offset) - 2;
-        self.real_hosts = real_hosts;
+        let new_cidr = if self.point_to_point && self.hosts <= 2 {
+            // A /31 for 2 hosts and a /32 for a single host
+            IPV4_BITS + 1 - self.hosts
+        } else {
+            Subnet::hosts_to_prefix(self.hosts)?
+        };
+        if new_cidr < self.cidr {
+            return Err(SubnetError::TooManyHosts {
+                hosts: self.hosts,
+                cidr: self.cidr,
+            });
+        }
+        let cidr_offset = IPV4_BITS - new_cidr;
+        let is_point_to_point = cidr_offset < MIN_HOST_BITS;
+
+        self.total_addresses = math::addresses(new_cidr);
+        self.real_hosts = math::usable_hosts(new_cidr);
 
-        let new_cidr = IPV4_BITS - cidr_offset;
         let new_mask = Subnet::cidr_to_mask(new_cidr)?;
+        self.wildcard = !new_mask;
 
-        self.broadcast = self.network.bitor(!new_mask);
+        self.broadcast = math::broadcast(self.network, new_cidr);
 
-        self.gateway = self.broadcast.bitand(Ipv4Addr::new(
-            MAX_OCTET_VALUE,
-            MAX_OCTET_VALUE,
-            MAX_OCTET_VALUE,
-            254,
-        ));
-        self.first_host = self.network.bitor(Ipv4Addr::new(0, 0, 0, 1));
-        self.last_host = self.broadcast.bitand(Ipv4Addr::new(
-            MAX_OCTET_VALUE,
-            MAX_OCTET_VALUE,
-            MAX_OCTET_VALUE,
-            253,
-        ));
+        if is_point_to_point {
+            // The broadcast is just the last address of the block, the other end of the link is the gateway
+            self.first_host = self.network;
+            self.last_host = self.broadcast;
+            self.gateway = self.broadcast;
+        } else {
+            match self.gateway_policy {
+                GatewayPolicy::First => {
+                    self.gateway = self.network.saturating_add(1);
+                    self.first_host = self.network.saturating_add(2);
+                    self.last_host = self.broadcast.saturating_sub(1);
+                }
+                GatewayPolicy::Last => {
+                    self.gateway = self.broadcast.bitand(Ipv4Addr::new(
+                        MAX_OCTET_VALUE,
+                        MAX_OCTET_VALUE,
+                        MAX_OCTET_VALUE,
+                        254,
+                    ));
+                    self.first_host = self.network.bitor(Ipv4Addr::new(0, 0, 0, 1));
+                    self.last_host = self.broadcast.bitand(Ipv4Addr::new(
+                        MAX_OCTET_VALUE,
+                        MAX_OCTET_VALUE,
+                        MAX_OCTET_VALUE,
+                        253,
+                    ));
+                }
+            }
+        }
 
-        self.next_subnet = self.broadcast.saturating_add(1);
+        self.next_subnet = u32::from(self.broadcast).checked_add(1).map(Ipv4Addr::from);
         self.next_cidr = new_cidr;
 
-        Ok(())
+        self.check_host_range()
+    }
+
+    /**
+     * Helper function to check that the gateway and the first and last hosts are hosts of the subnet,
+     * see [`Subnet::contains_host`] <br>
+     * They must be strictly between the network and the broadcast address, except in a /31 or a /32 (RFC 3021)
+     * where every address is usable
+     */
+    fn check_host_range(&self) -> Result<(), SubnetError> {
+        let addresses = [
+            ("gateway", self.gateway),
+            ("first host", self.first_host),
+            ("last host", self.last_host),
+        ];
+        match addresses
+            .into_iter()
+            .find(|&(_, address)| !self.contains_host(address))
+        {
+            Some((role, address)) => Err(SubnetError::HostOutsideSubnet {
+                role,
+                address,
+                network: self.network,
+                prefix: self.next_cidr,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /**
+     * Returns the discrepancies between the fields of the subnet and the ones recomputed from its network
+     * and its prefix length ([`Subnet::next_cidr`]), e.g. `broadcast is 10.0.0.64, expected 10.0.0.63` <br>
+     * It's empty for a calculated subnet, it's meant for the subnets loaded from a file, see [`crate::check_plan`] <br>
+     * The mask is the one of the parent network ([`Subnet::cidr`]), so it must be a valid mask no longer than the prefix <br>
+     * The gateway and the hosts are only checked to be hosts of the subnet, the gateway policy isn't saved
+     */
+    pub fn discrepancies(&self) -> Vec<String> {
+        let prefix = self.next_cidr;
+        let (Some(own_mask), Some(parent_mask)) =
+            (math::cidr_to_mask(prefix), math::cidr_to_mask(self.cidr))
+        else {
+            return vec![format!(
+                "prefix is /{} in a /{} network, expected at most /{}",
+                prefix, self.cidr, IPV4_BITS
+            )];
+        };
+
+        let mut discrepancies = Vec::new();
+        let mut compare = |field: &str, actual: String, expected: String| {
+            if actual != expected {
+                discrepancies.push(format!("{} is {}, expected {}", field, actual, expected));
+            }
+        };
+        compare(
+            "network",
+            self.network.to_string(),
+            math::network(self.network, prefix).to_string(),
+        );
+        let expected_mask = match math::mask_to_cidr(self.mask) {
+            None => "a valid mask".to_string(),
+            Some(mask_prefix) if mask_prefix > prefix => format!("at most a /{} mask", prefix),
+            Some(_) => parent_mask.to_string(),
+        };
+        compare("mask", self.mask.to_string(), expected_mask);
+        compare(
+            "wildcard",
+            self.wildcard.to_string(),
+            (!own_mask).to_string(),
+        );
+        compare(
+            "class",
+            self.class.to_string(),
+            math::class_of(self.network).to_string(),
+        );
+        compare(
+            "broadcast",
+            self.broadcast.to_string(),
+            math::broadcast(self.network, prefix).to_string(),
+        );
+        compare(
+            "usable hosts",
+            self.real_hosts.to_string(),
+            math::usable_hosts(prefix).to_string(),
+        );
+        compare(
+            "total addresses",
+            self.total_addresses.to_string(),
+            math::addresses(prefix).to_string(),
+        );
+        compare(
+            "next network",
+            format!("{:?}", self.next_subnet),
+            format!(
+                "{:?}",
+                u32::from(math::broadcast(self.network, prefix))
+                    .checked_add(1)
+                    .map(Ipv4Addr::from)
+            ),
+        );
+        if prefix < self.cidr {
+            discrepancies.push(format!(
+                "prefix is /{}, shorter than the /{} network",
+                prefix, self.cidr
+            ));
+        }
+        if let Err(error) = self.check_host_range() {
+            discrepancies.push(error.to_string());
+        }
+        discrepancies
+    }
+
+    /// Helper function to parse a number of hosts, rejecting non-numeric and out of range values
+    pub fn parse_hosts(hosts: &str) -> Result<u32, SubnetError> {
+        hosts
+            .trim()
+            .parse()
+            .map_err(|_| SubnetError::InvalidHosts(hosts.to_string()))
     }
 
-    /// Helper function to convert a string to an IPv4 address
+    /**
+     * Returns the prefix length of the smallest subnet with enough usable hosts for the number of hosts <br>
+     * Subnets smaller than a /30 have no usable hosts, so the longest prefix is /30 <br>
+     * Returns [`SubnetError::ZeroHosts`] for 0 hosts and [`SubnetError::TooManyHosts`] if it would need a prefix shorter than /0
+     */
+    pub fn hosts_to_prefix(hosts: u32) -> Result<u32, SubnetError> {
+        if hosts == 0 {
+            return Err(SubnetError::ZeroHosts);
+        }
+
+        // Requests above 2^32 - 2 hosts would need a prefix shorter than /0
+        math::hosts_to_prefix(hosts).ok_or(SubnetError::TooManyHosts { hosts, cidr: 0 })
+    }
+
+    /**
+     * Helper function to convert a string to an IPv4 address <br>
+     * The leading zeros of an octet are stripped when they don't change its value in octal (e.g. `192.168.001.000`),
+     * otherwise [`SubnetError::AmbiguousOctet`] is returned (e.g. `010`, which is 8 in octal) <br>
+     * The shorthands with fewer than 4 octets (e.g. `192.168.1`) are rejected with [`SubnetError::IncompleteIpAddress`],
+     * since they're expanded differently by different tools
+     */
     fn string_to_ip(ip: &str) -> Result<Ipv4Addr, SubnetError> {
-        ip.parse()
+        let octets: Vec<&str> = ip.split('.').collect();
+        let is_number =
+            |octet: &&str| !octet.is_empty() && octet.bytes().all(|b| b.is_ascii_digit());
+        if !octets.iter().all(is_number) {
+            return Err(SubnetError::InvalidIpAddress(ip.to_string()));
+        }
+        if octets.len() < 4 {
+            return Err(SubnetError::IncompleteIpAddress {
+                address: ip.to_string(),
+                octets: octets.len(),
+            });
+        }
+
+        let mut normalized = Vec::with_capacity(octets.len());
+        for octet in octets {
+            // e.g. 007 is 7 in both bases, but 010 is 10 in decimal and 8 in octal
+            let digits = octet.trim_start_matches('0');
+            let has_leading_zeros = octet.len() > 1 && digits.len() < octet.len();
+            if has_leading_zeros && digits.parse::<u32>().is_ok_and(|value| value >= 8) {
+                return Err(SubnetError::AmbiguousOctet {
+                    address: ip.to_string(),
+                    octet: octet.to_string(),
+                });
+            }
+            normalized.push(if digits.is_empty() { "0" } else { digits });
+        }
+
+        normalized
+            .join(".")
+            .parse()
             .map_err(|_| SubnetError::InvalidIpAddress(ip.to_string()))
     }
 
-    /// Helper function to convert a CIDR to a subnet mask
-    fn cidr_to_mask(cidr: u32) -> Result<Ipv4Addr, SubnetError> {
-        if cidr > IPV4_BITS {
-            return Err(SubnetError::InvalidCidr(cidr));
+    /// Converts a CIDR to a subnet mask (e.g. `20` to `255.255.240.0`), see [`math::cidr_to_mask`]
+    pub fn cidr_to_mask(cidr: u32) -> Result<Ipv4Addr, SubnetError> {
+        math::cidr_to_mask(cidr).ok_or(SubnetError::InvalidCidr(cidr))
+    }
+
+    /**
+     * Converts a subnet mask to a CIDR (e.g. `255.255.240.0` to `20`), counting the leading ones <br>
+     * Returns [`SubnetError::InvalidMask`] if the ones aren't contiguous (e.g. `255.0.255.0`)
+     */
+    pub fn mask_to_cidr(mask: Ipv4Addr) -> Result<u32, SubnetError> {
+        math::mask_to_cidr(mask).ok_or(SubnetError::InvalidMask(mask))
+    }
+
+    /// Returns true if the mask is a run of ones followed only by zeros (e.g. `255.255.255.0`, but not `255.255.0.255`)
+    pub fn is_valid_mask(mask: Ipv4Addr) -> bool {
+        math::is_valid_mask(mask)
+    }
+
+    /// Returns the network address of the subnet, i.e. the address with the host bits of the mask cleared
+    pub fn network_address(&self) -> Ipv4Addr {
+        self.network.bitand(self.mask)
+    }
+
+    /// Returns true if the address is inside the subnet, from the network to the broadcast address (inclusive)
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        self.network <= ip && ip <= self.broadcast
+    }
+
+    /**
+     * Returns true if the address is a host of the subnet, excluding the network and broadcast addresses <br>
+     * Every address of /31 and /32 subnets is a host
+     */
+    pub fn contains_host(&self, ip: Ipv4Addr) -> bool {
+        if self.next_cidr >= IPV4_BITS - 1 {
+            return self.contains(ip);
         }
+        self.network < ip && ip < self.broadcast
+    }
 
-        let mask = u32::MAX.checked_shl(IPV4_BITS - cidr).unwrap_or(0);
-        Ok(Ipv4Addr::from(mask))
+    /**
+     * Returns true if both subnets have the same prefix and their networks differ only in the last bit of the prefix,
+     * i.e. they're the two halves of the same supernet and can be merged, see [`Subnet::merge`]
+     */
+    pub fn is_sibling_of(&self, other: &Subnet) -> bool {
+        self.next_cidr == other.next_cidr
+            && math::are_siblings(self.network, other.network, self.next_cidr)
     }
 
-    /// Helper function to determine the class of the subnet
-    fn determine_class(cidr: u32) -> char {
-        match cidr {
-            0..=8 => 'A',
-            9..=16 => 'B',
-            17..=24 => 'C',
-            25..=32 => 'D',
-            _ => 'E',
+    /**
+     * Merges two sibling subnets into their supernet, one bit shorter (e.g. two /25 into a /24) <br>
+     * The hosts of the supernet are the hosts of both subnets, while the point-to-point and the gateway policy
+     * are the ones of this subnet <br>
+     * It returns None if the subnets aren't siblings, see [`Subnet::is_sibling_of`]
+     */
+    pub fn merge(&self, other: &Subnet) -> Option<Subnet> {
+        if !self.is_sibling_of(other) {
+            return None;
         }
+
+        let prefix = self.next_cidr - 1;
+        let network = self.network.min(other.network).to_string();
+        // A /31 only has usable hosts as a point-to-point link
+        let builder = if prefix == IPV4_BITS - 1 {
+            SubnetBuilder::new(&network, prefix, 2).point_to_point(true)
+        } else {
+            SubnetBuilder::new(&network, prefix, HostRequirement::Prefix(prefix).hosts())
+                .point_to_point(self.point_to_point)
+        };
+
+        let mut merged = builder.gateway_policy(self.gateway_policy).build().ok()?;
+        merged.hosts = self.hosts.saturating_add(other.hosts);
+        merged.requested_prefix = Some(prefix);
+        Some(merged)
     }
 
-    /// Helper function to convert the subnet information to a Markdown table
-    pub fn to_markdown_table(self) -> String {
-        format!(
-            "| **Network** | **Mask** | **CIDR** | **Class** | **Broadcast** | **Gateway** | **First Host** | **Last Host** | **Hosts** | **Real Hosts** | **Wasted Hosts** |\n| --- | --- | --- | --- | --- | --- | --- | --- | --- | --- | --- |\n| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |",
-            self.network,
-            self.mask,
-            self.cidr,
-            self.class,
-            self.broadcast,
-            self.gateway,
-            self.first_host,
-            self.last_host,
-            self.hosts,
-            self.real_hosts,
-            self.real_hosts + 2 - self.hosts
-        )
+    /**
+     * Describes a known network in CIDR notation or with a mask (e.g. `192.168.5.0/26`), without allocating it <br>
+     * The subnet is calculated for every usable host of the prefix, as if it was requested by prefix length,
+     * a /31 and a /32 being point-to-point links <br>
+     * Returns the errors of [`NetworkSpec`] and [`SubnetError::NotANetworkAddress`] if the address has host bits set
+     */
+    pub fn describe(network: &str) -> Result<Subnet, SubnetError> {
+        let spec: NetworkSpec = network.parse()?;
+        let hosts = math::usable_hosts(spec.cidr);
+        let mut subnet = SubnetBuilder::new(&spec.network.to_string(), spec.cidr, hosts)
+            .point_to_point(spec.cidr >= IPV4_BITS - 1)
+            .build()?;
+        subnet.requested_prefix = Some(spec.cidr);
+        Ok(subnet)
     }
-}
 
-/// Implements the Display trait for the Subnet struct to print the subnet information (markdown format)
-impl Display for Subnet {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "\n## Subnet Info:\n\t - Network: {}\n\t - Mask: {}\n\t - CIDR: {}\n\t - Class: {}\n\t - Broadcast: {}\n\t - Gateway: {}\n\t - First Host: {}\n\t - Last Host: {}\n\t - Hosts: {}\n\t - Real Hosts: {}\n\t - Wasted Hosts: {}",
-            self.network,
-            self.mask,
-            self.cidr,
+    /**
+     * Returns an iterator over the usable host addresses of the subnet, the gateway included <br>
+     * They're the addresses between the network and broadcast addresses, or both addresses of a /31 link,
+     * so there are [`Subnet::real_hosts`] of them, except for a /32 which has no host to yield <br>
+     * It yields nothing if the subnet hasn't been calculated
+     */
+    pub fn hosts_iter(&self) -> impl Iterator<Item = Ipv4Addr> {
+        let network = u32::from(self.network);
+        let broadcast = u32::from(self.broadcast);
+        // The total addresses are only set by the calculation, an empty range is first > last
+        let (first, last) = if self.total_addresses == 0 || self.next_cidr == IPV4_BITS {
+            (1, 0)
+        } else if self.next_cidr == IPV4_BITS - 1 {
+            (network, broadcast)
+        } else {
+            (network + 1, broadcast - 1)
+        };
+        (first..=last).map(Ipv4Addr::from)
+    }
+
+    /**
+     * Returns the first and the last address of the DHCP pool of the subnet, i.e. the usable hosts without the gateway <br>
+     * The gateway is carved out of the start or the end of the usable hosts, following the [`GatewayPolicy`] <br>
+     * Point-to-point subnets (/31 and /32) have no gateway to carve out, so the pool is the whole subnet
+     */
+    pub fn dhcp_range(&self) -> (Ipv4Addr, Ipv4Addr) {
+        if self.next_cidr >= IPV4_BITS - 1 {
+            return (self.network, self.broadcast);
+        }
+
+        let (first, last) = (
+            self.network.saturating_add(1),
+            self.broadcast.saturating_sub(1),
+        );
+        match self.gateway_policy {
+            GatewayPolicy::First => (first.saturating_add(1), last),
+            GatewayPolicy::Last => (first, last.saturating_sub(1)),
+        }
+    }
+
+    /**
+     * Returns the number of addresses of the subnet that aren't used by the requested hosts <br>
+     * The network and broadcast addresses are counted too, except for /31 and /32 subnets where every address is usable
+     */
+    pub fn wasted_hosts(&self) -> u32 {
+        let addresses = if self.next_cidr >= IPV4_BITS - 1 {
+            self.real_hosts
+        } else {
+            self.real_hosts.saturating_add(2)
+        };
+        addresses.saturating_sub(self.hosts)
+    }
+
+    /// Returns the percentage of the addresses of the subnet that are wasted, see [`Subnet::wasted_hosts`]
+    pub fn wasted_percent(&self) -> f64 {
+        if self.total_addresses == 0 {
+            return 0.0;
+        }
+        f64::from(self.wasted_hosts()) * 100.0 / self.total_addresses as f64
+    }
+
+    /**
+     * Returns the name of the reverse DNS zone of the subnet (e.g. `1.168.192.in-addr.arpa` for `192.168.1.0/24`) <br>
+     * Prefixes longer than /24 use the RFC 2317 classless delegation naming, where the last octet
+     * is followed by the prefix length (e.g. `32/27.1.168.192.in-addr.arpa` for `192.168.1.32/27`) <br>
+     * RFC 2317 doesn't cover the shorter prefixes that aren't octet-aligned, so they get the enclosing octet-aligned zone
+     * (e.g. `1.10.in-addr.arpa` for `10.1.16.0/20`), the subnet being delegated as one `/24` zone per third octet
+     */
+    pub fn reverse_dns_zone(&self) -> String {
+        let octets = self.network.octets();
+        let full_octets = (self.next_cidr / 8) as usize;
+
+        let mut labels: Vec<String> = octets[..full_octets]
+            .iter()
+            .rev()
+            .map(|octet| octet.to_string())
+            .collect();
+        if self.next_cidr > 24 && !self.next_cidr.is_multiple_of(8) {
+            labels.insert(0, format!("{}/{}", octets[full_octets], self.next_cidr));
+        }
+
+        labels.push("in-addr.arpa".to_string());
+        labels.join(".")
+    }
+
+    /// Returns whether the network is private, loopback, link-local, public, etc.
+    pub fn address_scope(&self) -> AddressScope {
+        AddressScope::of(self.network)
+    }
+
+    /**
+     * Returns the index (0 to 3) of the interesting octet, the octet of the mask holding the last network bit <br>
+     * e.g. 3 (the 4th octet) for a /26 and 2 (the 3rd octet) for a /20 or a /24
+     */
+    pub fn interesting_octet(&self) -> usize {
+        math::interesting_octet(self.next_cidr)
+    }
+
+    /**
+     * Returns the number of bits borrowed from the host bits of the classful default mask
+     * (/8 for class A, /16 for class B and /24 for class C), e.g. 2 for a /26 in a class C network <br>
+     * It's 0 for the prefixes shorter than the default mask and for the classes D and E, which have no default mask
+     */
+    pub fn borrowed_bits(&self) -> u32 {
+        math::default_prefix(self.class).map_or(0, |default_prefix| {
+            self.next_cidr.saturating_sub(default_prefix)
+        })
+    }
+
+    /**
+     * Returns a note comparing the prefix with the classful default mask of the network's class,
+     * e.g. `Class C network subnetted to /26 (2 bits borrowed)` <br>
+     * It's `None` for the default mask itself and for the classes D and E
+     */
+    pub fn class_note(&self) -> Option<String> {
+        let default_prefix = math::default_prefix(self.class)?;
+        let (action, bits, verb) = match self.next_cidr.cmp(&default_prefix) {
+            std::cmp::Ordering::Greater => {
+                ("subnetted", self.next_cidr - default_prefix, "borrowed")
+            }
+            std::cmp::Ordering::Less => {
+                ("supernetted", default_prefix - self.next_cidr, "given back")
+            }
+            std::cmp::Ordering::Equal => return None,
+        };
+        Some(format!(
+            "Class {} network {} to /{} ({} bit{} {})",
             self.class,
-            self.broadcast,
-            self.gateway,
-            self.first_host,
-            self.last_host,
-            self.hosts,
-            self.real_hosts,
-            self.real_hosts + 2 - self.hosts
-        )
+            action,
+            self.next_cidr,
+            bits,
+            if bits == 1 { "" } else { "s" },
+            verb
+        ))
+    }
+
+    /**
+     * Returns the block size (or magic number) of the subnet, i.e. `256` minus the interesting octet of the mask <br>
+     * It's the increment between the networks in the interesting octet, see [`Subnet::interesting_octet`]
+     */
+    pub fn block_size(&self) -> u32 {
+        math::block_size(self.next_cidr)
+    }
+
+    /// Returns the number of bits of the wildcard mask set to 1, i.e. the host bits of the subnet (e.g. 8 for a /24)
+    pub fn wildcard_bits(&self) -> u32 {
+        u32::from(self.wildcard).count_ones()
+    }
+
+    /**
+     * Returns a ready-to-paste access list line permitting the traffic from the subnet to any destination
+     * (e.g. `access-list permit ip 10.1.1.0 0.0.0.255 any` for [`AclStyle::CiscoIos`]) <br>
+     * For another syntax, render a custom template with the `{network}`, `{wildcard}` and `{prefix}` fields, see [`Subnet::render`]
+     */
+    pub fn acl_line(&self, style: AclStyle) -> String {
+        self.render(style.template())
+            .expect("the templates of the ACL styles are valid")
+    }
+
+    /**
+     * Renders the subnet with a template, replacing each `{field}` with the value of the field
+     * (e.g. `{network}/{prefix} gw={gateway}` gives `192.168.1.0/26 gw=192.168.1.62`) <br>
+     * The fields are the ones of the JSON output, see [`Subnet::to_json_value`], and the names of the struct fields
+     * are accepted too (e.g. `next_cidr` for `prefix`) <br>
+     * The braces are escaped by doubling them (`{{` and `}}`) <br>
+     * Returns [`SubnetError::InvalidTemplate`] for unknown fields and unmatched braces
+     */
+    pub fn render(&self, template: &str) -> Result<String, SubnetError> {
+        let mut rendered = String::with_capacity(template.len());
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    rendered.push('{');
+                }
+                '{' => {
+                    let (field, rest) = chars.as_str().split_once('}').ok_or_else(|| {
+                        SubnetError::InvalidTemplate(format!("{{{}", chars.as_str()))
+                    })?;
+                    let value = self
+                        .field(field.trim())
+                        .ok_or_else(|| SubnetError::InvalidTemplate(format!("{{{}}}", field)))?;
+                    rendered.push_str(&value);
+                    chars = rest.chars();
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    rendered.push('}');
+                }
+                '}' => return Err(SubnetError::InvalidTemplate("}".to_string())),
+                c => rendered.push(c),
+            }
+        }
+
+        Ok(rendered)
+    }
+
+    /// Helper function to get the value of a field by its name for [`Subnet::render`]
+    fn field(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "name" => self.name.clone().unwrap_or_default(),
+            "network" => self.network.to_string(),
+            "mask" => self.mask.to_string(),
+            "wildcard" => self.wildcard.to_string(),
+            "wildcard_bits" => self.wildcard_bits().to_string(),
+            "network_u32" => self.network_u32().to_string(),
+            "network_hex" => Subnet::ip_to_hex(self.network),
+            "broadcast_u32" => self.broadcast_u32().to_string(),
+            "broadcast_hex" => Subnet::ip_to_hex(self.broadcast),
+            "class" => self.class.to_string(),
+            "cidr" => self.cidr.to_string(),
+            "first_host" => self.first_host.to_string(),
+            "last_host" => self.last_host.to_string(),
+            "broadcast" => self.broadcast.to_string(),
+            "gateway" => self.gateway.to_string(),
+            "hosts" => self.hosts.to_string(),
+            "usable_hosts" | "real_hosts" => self.real_hosts.to_string(),
+            "total_addresses" => self.total_addresses.to_string(),
+            "next_network" | "next_subnet" => self
+                .next_subnet
+                .map_or_else(String::new, |next| next.to_string()),
+            "prefix" | "next_cidr" => self.next_cidr.to_string(),
+            "wasted_hosts" => self.wasted_hosts().to_string(),
+            "address_scope" | "scope" => self.address_scope().to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /**
+     * Returns the subnet as a JSON object, with the same keys as the JSON files <br>
+     * The keys are stable and don't follow the field names (e.g. `usable_hosts` for [`Subnet::real_hosts`]),
+     * and the derived [`Subnet::wasted_hosts`] and [`Subnet::address_scope`] are included,
+     * followed by the [`Subnet::extra`] tags if there are any
+     */
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).expect("a subnet is always serializable");
+        value["wasted_hosts"] = self.wasted_hosts().into();
+        value["address_scope"] = serde_json::to_value(self.address_scope())
+            .expect("an address scope is always serializable");
+        if !self.extra.is_empty() {
+            value["extra"] =
+                serde_json::to_value(&self.extra).expect("the tags are always serializable");
+        }
+        value
+    }
+
+    /// Returns the subnet information with the amount of detail of the verbosity, see [`Verbosity`]
+    pub fn format_with(&self, verbosity: Verbosity) -> String {
+        match verbosity {
+            Verbosity::Compact => self.summary(),
+            Verbosity::Normal => format!("{}", self),
+            Verbosity::Detailed => format!("{:#}", self),
+        }
+    }
+
+    /// Returns a one-line summary of the subnet (e.g. `10.0.0.0/26  hosts 62  bcast 10.0.0.63  gw 10.0.0.62`)
+    pub fn summary(&self) -> String {
+        format!(
+            "{}/{}  hosts {}  bcast {}  gw {}",
+            self.network, self.next_cidr, self.real_hosts, self.broadcast, self.gateway
+        )
+    }
+
+    /**
+     * Returns the steps of the calculation of the subnet, from the requested hosts to the range of addresses <br>
+     * e.g. `Need 50 hosts → 50 + 2 (network and broadcast) = 52 addresses → next power of two 64 → 6 host bits → /26 → mask 255.255.255.192 → range 10.0.0.0 - 10.0.0.63` <br>
+     * The steps are derived from the calculated fields, so the subnet must be calculated first
+     */
+    pub fn explain(&self) -> String {
+        let host_bits = IPV4_BITS - self.next_cidr;
+        let mut steps = Vec::new();
+
+        match (self.requested_prefix, self.requested_hosts) {
+            (Some(prefix), _) => steps.push(format!("Requested /{}", prefix)),
+            (None, Some(hosts)) => steps.push(format!(
+                "Need {} hosts plus growth = {} hosts",
+                hosts, self.hosts
+            )),
+            (None, None) => steps.push(format!("Need {} hosts", self.hosts)),
+        }
+        if self.requested_prefix.is_none() {
+            if host_bits < MIN_HOST_BITS {
+                steps.push(format!(
+                    "point-to-point link without network and broadcast = {} addresses",
+                    self.hosts
+                ));
+            } else {
+                steps.push(format!(
+                    "{} + 2 (network and broadcast) = {} addresses",
+                    self.hosts,
+                    self.hosts as u64 + 2
+                ));
+            }
+            steps.push(format!("next power of two {}", self.total_addresses));
+        }
+        steps.push(format!(
+            "{} host bit{}",
+            host_bits,
+            if host_bits == 1 { "" } else { "s" }
+        ));
+        if self.requested_prefix.is_some() {
+            steps.push(format!(
+                "2^{} = {} addresses",
+                host_bits, self.total_addresses
+            ));
+        } else {
+            steps.push(format!("/{}", self.next_cidr));
+        }
+        steps.push(format!("mask {}", !self.wildcard));
+        steps.push(format!("range {} - {}", self.network, self.broadcast));
+
+        steps.join(" → ")
+    }
+
+    /**
+     * Returns the binary representation of the subnet mask (e.g. `11111111.11111111.11111111.00000000`) <br>
+     * It's the mask of the calculated subnet itself ([`Subnet::next_cidr`]), not [`Subnet::mask`] of the parent network
+     */
+    pub fn to_binary_string(&self) -> String {
+        Subnet::ip_to_binary(!self.wildcard)
+    }
+
+    /// Helper function to convert an IPv4 address to dot-separated, zero-padded binary octets
+    fn ip_to_binary(ip: Ipv4Addr) -> String {
+        ip.octets()
+            .iter()
+            .map(|octet| format!("{:08b}", octet))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Returns the network address as a 32-bit integer (e.g. `167772160` for `10.0.0.0`)
+    pub fn network_u32(&self) -> u32 {
+        u32::from(self.network)
+    }
+
+    /// Returns the broadcast address as a 32-bit integer (e.g. `167772415` for `10.0.0.255`)
+    pub fn broadcast_u32(&self) -> u32 {
+        u32::from(self.broadcast)
+    }
+
+    /// Helper function to convert an IPv4 address to a zero-padded, uppercase hexadecimal integer (e.g. `0x0A000000`)
+    fn ip_to_hex(ip: Ipv4Addr) -> String {
+        format!("0x{:08X}", u32::from(ip))
+    }
+
+    /// Helper function to print an IPv4 address as a decimal and a hexadecimal integer (e.g. `167772160 (0x0A000000)`)
+    fn ip_to_integers(ip: Ipv4Addr) -> String {
+        format!("{} ({})", u32::from(ip), Subnet::ip_to_hex(ip))
+    }
+
+    /// Helper function to convert the subnet information to a Markdown table
+    pub fn to_markdown_table(&self) -> String {
+        format!(
+            "| **{}** |\n|{}\n| {} |",
+            TABLE_COLUMNS.join("** | **"),
+            " --- |".repeat(TABLE_COLUMNS.len()),
+            self.table_row().join(" | ")
+        )
+    }
+
+    /// Returns the values of the [`TABLE_COLUMNS`] of the subnet, used by the Markdown and HTML tables
+    pub(crate) fn table_row(&self) -> [String; 17] {
+        [
+            self.name.clone().unwrap_or_default(),
+            self.network.to_string(),
+            self.mask.to_string(),
+            self.wildcard.to_string(),
+            self.class.to_string(),
+            self.cidr.to_string(),
+            self.first_host.to_string(),
+            self.last_host.to_string(),
+            self.broadcast.to_string(),
+            self.gateway.to_string(),
+            self.hosts.to_string(),
+            self.real_hosts.to_string(),
+            self.total_addresses.to_string(),
+            self.next_subnet
+                .map(|ip| ip.to_string())
+                .unwrap_or_default(),
+            self.next_cidr.to_string(),
+            self.wasted_hosts().to_string(),
+            self.address_scope().to_string(),
+        ]
+    }
+}
+
+/**
+ * Implements the Display trait for the Subnet struct to print the subnet information (markdown format) <br>
+ * The alternate flag (`{:#}`) also prints the network and the mask in binary,
+ * and the network, the broadcast and the hosts as decimal and hexadecimal integers
+ */
+impl Display for Subnet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\n## Subnet Info:{}\n\t - Network: {}\n\t - Mask: {}\n\t - Wildcard: {}\n\t - CIDR: {}\n\t - Class: {}\n\t - Scope: {}\n\t - Broadcast: {}\n\t - Gateway: {}\n\t - First Host: {}\n\t - Last Host: {}\n\t - Hosts: {}{}\n\t - Real Hosts: {}\n\t - Total Addresses: {}\n\t - Wasted Hosts: {}",
+            self.name
+                .as_ref()
+                .map_or(String::new(), |name| format!("\n\t - Name: {}", name)),
+            self.network,
+            self.mask,
+            self.wildcard,
+            self.cidr,
+            self.class,
+            self.address_scope(),
+            self.broadcast,
+            self.gateway,
+            self.first_host,
+            self.last_host,
+            self.hosts,
+            match (self.requested_prefix, self.requested_hosts) {
+                (Some(prefix), _) => format!(" (requested as /{})", prefix),
+                (None, Some(hosts)) => format!(" (requested {} plus growth)", hosts),
+                (None, None) => String::new(),
+            },
+            self.real_hosts,
+            self.total_addresses,
+            self.wasted_hosts()
+        )?;
+
+        if let Some(note) = self.class_note() {
+            write!(f, "\n\t - Note: {}", note)?;
+        }
+
+        if f.alternate() {
+            write!(
+                f,
+                "\n\t - Network (binary): {}\n\t - Mask (binary): {}\n\t - Block Size: {} in the {} octet\n\t - Borrowed Bits: {}",
+                Subnet::ip_to_binary(self.network),
+                self.to_binary_string(),
+                self.block_size(),
+                ["1st", "2nd", "3rd", "4th"][self.interesting_octet()],
+                self.borrowed_bits()
+            )?;
+            write!(
+                f,
+                "\n\t - Network (integer): {}\n\t - Broadcast (integer): {}\n\t - Hosts (integer): {} - {}",
+                Subnet::ip_to_integers(self.network),
+                Subnet::ip_to_integers(self.broadcast),
+                Subnet::ip_to_integers(self.first_host),
+                Subnet::ip_to_integers(self.last_host)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn determine_class_uses_first_octet() {
+        assert_eq!(math::class_of(Ipv4Addr::new(0, 0, 0, 0)), 'A');
+        assert_eq!(math::class_of(Ipv4Addr::new(127, 0, 0, 0)), 'A');
+        assert_eq!(math::class_of(Ipv4Addr::new(128, 0, 0, 0)), 'B');
+        assert_eq!(math::class_of(Ipv4Addr::new(191, 255, 0, 0)), 'B');
+        assert_eq!(math::class_of(Ipv4Addr::new(192, 0, 0, 0)), 'C');
+        assert_eq!(math::class_of(Ipv4Addr::new(223, 255, 255, 0)), 'C');
+        assert_eq!(math::class_of(Ipv4Addr::new(224, 0, 0, 0)), 'D');
+        assert_eq!(math::class_of(Ipv4Addr::new(239, 255, 255, 0)), 'D');
+        assert_eq!(math::class_of(Ipv4Addr::new(240, 0, 0, 0)), 'E');
+        assert_eq!(math::class_of(Ipv4Addr::new(255, 255, 255, 255)), 'E');
+    }
+
+    #[test]
+    fn class_a_network_with_long_prefix() {
+        let subnet = Subnet::new("10.0.0.0", 24, 10).unwrap();
+        assert_eq!(subnet.class, 'A');
+    }
+
+    #[test]
+    fn wildcard_is_inverse_of_recalculated_mask() {
+        let mut subnet = Subnet::new("192.168.0.0", 16, 254).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.wildcard, Ipv4Addr::new(0, 0, 0, 255));
+    }
+
+    #[test]
+    fn wasted_hosts_of_slash_25() {
+        let mut subnet = Subnet::new("10.0.0.0", 24, 100).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.real_hosts, 126);
+        assert_eq!(subnet.wasted_hosts(), 28);
+    }
+
+    #[test]
+    fn explain_steps_of_50_hosts() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 50).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(
+            subnet.explain(),
+            "Need 50 hosts → 50 + 2 (network and broadcast) = 52 addresses → next power of two 64 \
+             → 6 host bits → /26 → mask 255.255.255.192 → range 192.168.1.0 - 192.168.1.63"
+        );
+    }
+
+    #[test]
+    fn wasted_hosts_near_boundary_doesnt_panic() {
+        let mut subnet = Subnet::new("10.0.0.0", 24, 126).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 25);
+        assert_eq!(subnet.wasted_hosts(), 2);
+        assert!(subnet.to_string().contains("Wasted Hosts: 2"));
+        assert!(subnet
+            .to_markdown_table()
+            .ends_with(&format!("| 2 | {} |", subnet.address_scope())));
+
+        let uncalculated = Subnet::new("10.0.0.0", 24, 100).unwrap();
+        assert_eq!(uncalculated.wasted_hosts(), 0);
+    }
+
+    #[test]
+    fn point_to_point_wastes_nothing() {
+        let mut subnet = Subnet::new("10.0.0.0", 24, 2).unwrap();
+        subnet.point_to_point = true;
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.wasted_hosts(), 0);
+    }
+
+    #[test]
+    fn format_at_each_verbosity() {
+        let subnet = Subnet::describe("192.168.1.0/26").unwrap();
+
+        assert_eq!(
+            subnet.format_with(Verbosity::Compact),
+            "192.168.1.0/26  hosts 62  bcast 192.168.1.63  gw 192.168.1.62"
+        );
+
+        let normal = subnet.format_with(Verbosity::Normal);
+        assert!(normal.contains("\t - Network: 192.168.1.0"));
+        assert!(normal.contains("\t - Scope: Private"));
+        assert!(!normal.contains("Mask (binary)"));
+
+        let detailed = subnet.format_with(Verbosity::Detailed);
+        assert!(detailed.starts_with(&normal));
+        assert!(detailed.contains("\t - Mask (binary): 11111111.11111111.11111111.11000000"));
+        assert!(detailed.contains("\t - Block Size: 64 in the 4th octet"));
+
+        assert_eq!(
+            "Detailed".parse::<Verbosity>().unwrap(),
+            Verbosity::Detailed
+        );
+        assert!("loud".parse::<Verbosity>().is_err());
+    }
+
+    #[test]
+    fn binary_mask_of_allocated_subnet() {
+        // A /26 allocated in a /24 shows its own mask, matching its block size and borrowed bits
+        let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+        let detailed = subnet.format_with(Verbosity::Detailed);
+        assert!(detailed.contains("\t - Mask (binary): 11111111.11111111.11111111.11000000"));
+        assert!(detailed.contains("\t - Block Size: 64 in the 4th octet"));
+        assert!(detailed.contains("\t - Borrowed Bits: 2"));
+    }
+
+    #[test]
+    fn network_as_integer_and_hex() {
+        let subnet = Subnet::describe("10.0.0.0/24").unwrap();
+        assert_eq!(subnet.network_u32(), 167772160);
+        assert_eq!(Subnet::ip_to_hex(subnet.network), "0x0A000000");
+        assert_eq!(
+            subnet
+                .render("{network_u32} {network_hex} {broadcast_hex}")
+                .unwrap(),
+            "167772160 0x0A000000 0x0A0000FF"
+        );
+
+        let verbose = format!("{:#}", subnet);
+        assert!(verbose.contains("Network (integer): 167772160 (0x0A000000)"));
+        assert!(verbose.contains("Broadcast (integer): 167772415 (0x0A0000FF)"));
+    }
+
+    #[test]
+    fn mask_to_binary_string() {
+        // A /26 allocated in a /24 has its own 255.255.255.192 mask, not the 255.255.255.0 of the parent
+        let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+        assert_eq!(subnet.mask, Ipv4Addr::new(255, 255, 255, 0));
+        assert_eq!(
+            subnet.to_binary_string(),
+            "11111111.11111111.11111111.11000000"
+        );
+    }
+
+    #[test]
+    fn binary_only_in_alternate_display() {
+        let mut subnet = Subnet::new("10.0.0.0", 24, 10).unwrap();
+        subnet.calculate().unwrap();
+        assert!(!format!("{}", subnet).contains("binary"));
+        assert!(format!("{:#}", subnet)
+            .contains("Network (binary): 00001010.00000000.00000000.00000000"));
+    }
+
+    #[test]
+    fn host_address_is_not_a_network_address() {
+        assert!(matches!(
+            Subnet::new("192.168.1.37", 24, 10),
+            Err(SubnetError::NotANetworkAddress {
+                network,
+                cidr: 24,
+                ..
+            }) if network == Ipv4Addr::new(192, 168, 1, 0)
+        ));
+    }
+
+    #[test]
+    fn network_address_of_subnet() {
+        let subnet = Subnet::new("192.168.1.64", 26, 10).unwrap();
+        assert_eq!(subnet.network_address(), Ipv4Addr::new(192, 168, 1, 64));
+    }
+
+    #[test]
+    fn gateway_last_by_default() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 200).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.gateway, Ipv4Addr::new(192, 168, 1, 254));
+        assert_eq!(subnet.first_host, Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(subnet.last_host, Ipv4Addr::new(192, 168, 1, 253));
+    }
+
+    #[test]
+    fn gateway_first() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 200).unwrap();
+        subnet.gateway_policy = GatewayPolicy::First;
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.gateway, Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(subnet.first_host, Ipv4Addr::new(192, 168, 1, 2));
+        assert_eq!(subnet.last_host, Ipv4Addr::new(192, 168, 1, 254));
+    }
+
+    #[test]
+    fn describe_known_network() {
+        let subnet = Subnet::describe("192.168.5.0/26").unwrap();
+        assert_eq!(subnet.broadcast, Ipv4Addr::new(192, 168, 5, 63));
+        assert_eq!(subnet.next_cidr, 26);
+        assert_eq!(subnet.real_hosts, 62);
+        assert_eq!(subnet.first_host, Ipv4Addr::new(192, 168, 5, 1));
+
+        let link = Subnet::describe("10.0.0.4/31").unwrap();
+        assert_eq!(link.next_cidr, 31);
+        assert_eq!(link.real_hosts, 2);
+
+        assert!(matches!(
+            Subnet::describe("192.168.5.10/26"),
+            Err(SubnetError::NotANetworkAddress { .. })
+        ));
+    }
+
+    #[test]
+    fn dhcp_range_without_last_gateway() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 200).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(
+            subnet.dhcp_range(),
+            (
+                Ipv4Addr::new(192, 168, 1, 1),
+                Ipv4Addr::new(192, 168, 1, 253)
+            )
+        );
+    }
+
+    #[test]
+    fn dhcp_range_without_first_gateway() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 200).unwrap();
+        subnet.gateway_policy = GatewayPolicy::First;
+        subnet.calculate().unwrap();
+        assert_eq!(
+            subnet.dhcp_range(),
+            (
+                Ipv4Addr::new(192, 168, 1, 2),
+                Ipv4Addr::new(192, 168, 1, 254)
+            )
+        );
+    }
+
+    #[test]
+    fn gateway_policy_from_str() {
+        assert_eq!(
+            "First".parse::<GatewayPolicy>().unwrap(),
+            GatewayPolicy::First
+        );
+        assert_eq!(
+            "last".parse::<GatewayPolicy>().unwrap(),
+            GatewayPolicy::Last
+        );
+        assert!(matches!(
+            "middle".parse::<GatewayPolicy>(),
+            Err(SubnetError::InvalidGatewayPolicy(_))
+        ));
+    }
+
+    #[test]
+    fn contains_addresses_of_the_range() {
+        let mut subnet = Subnet::new("192.168.1.64", 26, 50).unwrap();
+        subnet.calculate().unwrap();
+
+        let host = Ipv4Addr::new(192, 168, 1, 100);
+        assert!(subnet.contains(host));
+        assert!(subnet.contains_host(host));
+
+        assert!(subnet.contains(subnet.network));
+        assert!(!subnet.contains_host(subnet.network));
+
+        assert!(subnet.contains(subnet.broadcast));
+        assert!(!subnet.contains_host(subnet.broadcast));
+
+        let outside = Ipv4Addr::new(192, 168, 1, 128);
+        assert!(!subnet.contains(outside));
+        assert!(!subnet.contains_host(outside));
+    }
+
+    #[test]
+    fn reverse_dns_zone_of_slash_24() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 200).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.reverse_dns_zone(), "1.168.192.in-addr.arpa");
+    }
+
+    #[test]
+    fn reverse_dns_zone_of_slash_16() {
+        let mut subnet = Subnet::new("172.16.0.0", 16, 60000).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 16);
+        assert_eq!(subnet.reverse_dns_zone(), "16.172.in-addr.arpa");
+    }
+
+    #[test]
+    fn reverse_dns_zone_of_slash_27_is_classless() {
+        let mut subnet = Subnet::new("192.168.1.32", 27, 30).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 27);
+        assert_eq!(subnet.reverse_dns_zone(), "32/27.1.168.192.in-addr.arpa");
+    }
+
+    #[test]
+    fn reverse_dns_zone_of_slash_20_is_the_enclosing_zone() {
+        let mut subnet = Subnet::new("10.1.16.0", 20, 4000).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 20);
+        assert_eq!(subnet.reverse_dns_zone(), "1.10.in-addr.arpa");
+    }
+
+    #[test]
+    fn no_next_subnet_at_top_of_address_space() {
+        let mut subnet = Subnet::new("255.255.255.0", 24, 200).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.broadcast, Ipv4Addr::BROADCAST);
+        assert_eq!(subnet.next_subnet, None);
+    }
+
+    #[test]
+    fn builder_returns_calculated_subnet() {
+        let subnet = SubnetBuilder::new("10.0.0.0", 24, 2)
+            .point_to_point(true)
+            .gateway_policy(GatewayPolicy::First)
+            .build()
+            .unwrap();
+        assert_eq!(subnet.next_cidr, 31);
+        assert_eq!(subnet.broadcast, Ipv4Addr::new(10, 0, 0, 1));
+
+        assert!(matches!(
+            SubnetBuilder::new("10.0.0.0", 24, 0).build(),
+            Err(SubnetError::ZeroHosts)
+        ));
+    }
+
+    #[test]
+    fn host_requirement_from_str() {
+        assert_eq!(
+            "/28".parse::<HostRequirement>().unwrap(),
+            HostRequirement::Prefix(28)
+        );
+        assert_eq!(
+            " 50 ".parse::<HostRequirement>().unwrap(),
+            HostRequirement::Hosts(50)
+        );
+        assert_eq!(HostRequirement::Prefix(28).hosts(), 14);
+        assert!(matches!(
+            "/31".parse::<HostRequirement>(),
+            Err(SubnetError::InvalidCidr(31))
+        ));
+        assert!(matches!(
+            "/abc".parse::<HostRequirement>(),
+            Err(SubnetError::InvalidHosts(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_cidr_as_json() {
+        let error = Subnet::new("192.168.1.0", 33, 10).unwrap_err();
+        assert_eq!(error.exit_code(), 11);
+
+        let value: serde_json::Value = serde_json::from_str(&error.to_json()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "error": "InvalidCidr", "detail": "33" })
+        );
+    }
+
+    #[test]
+    fn total_addresses_of_slash_26() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 50).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 26);
+        assert_eq!(subnet.total_addresses, 64);
+        assert_eq!(subnet.real_hosts, 62);
+    }
+
+    #[test]
+    fn network_spec_from_str() {
+        let spec: NetworkSpec = "192.168.1.0/24".parse().unwrap();
+        assert_eq!(spec.network, Ipv4Addr::new(192, 168, 1, 0));
+        assert_eq!(spec.cidr, 24);
+        assert_eq!(spec.to_string(), "192.168.1.0/24");
+    }
+
+    #[test]
+    fn network_spec_missing_slash() {
+        assert!(matches!(
+            "192.168.1.0".parse::<NetworkSpec>(),
+            Err(SubnetError::MissingCidr(_))
+        ));
+    }
+
+    #[test]
+    fn network_spec_with_classful_default() {
+        for (address, cidr) in [("10.0.0.0", 8), ("172.16.0.0", 16), ("192.168.1.0", 24)] {
+            let spec = NetworkSpec::parse_classful(address).unwrap();
+            assert_eq!(
+                (spec.network.to_string().as_str(), spec.cidr),
+                (address, cidr)
+            );
+        }
+        assert_eq!(NetworkSpec::parse_classful("10.0.0.0/24").unwrap().cidr, 24);
+        assert!(matches!(
+            NetworkSpec::parse_classful("224.0.0.0"),
+            Err(SubnetError::MissingCidr(_))
+        ));
+    }
+
+    #[test]
+    fn network_spec_cidr_out_of_range() {
+        assert!(matches!(
+            "192.168.1.0/33".parse::<NetworkSpec>(),
+            Err(SubnetError::InvalidCidr(33))
+        ));
+        assert!(matches!(
+            "192.168.1.0/abc".parse::<NetworkSpec>(),
+            Err(SubnetError::InvalidCidrFormat(_))
+        ));
+    }
+
+    #[test]
+    fn network_spec_negative_cidr() {
+        assert!(matches!(
+            "192.168.1.0/-1".parse::<NetworkSpec>(),
+            Err(SubnetError::InvalidCidrFormat(cidr)) if cidr == "-1"
+        ));
+    }
+
+    #[test]
+    fn network_spec_surrounding_whitespace() {
+        let spec: NetworkSpec = "  192.168.1.0 / 24 \n".parse().unwrap();
+        assert_eq!(spec.to_string(), "192.168.1.0/24");
+        assert!(matches!(
+            "192.168.1.0/ 35 ".parse::<NetworkSpec>(),
+            Err(SubnetError::InvalidCidr(35))
+        ));
+    }
+
+    #[test]
+    fn network_spec_garbage_ip() {
+        assert!(matches!(
+            "not.an.ip/24".parse::<NetworkSpec>(),
+            Err(SubnetError::InvalidIpAddress(_))
+        ));
+    }
+
+    #[test]
+    fn hosts_to_prefix_of_several_counts() {
+        assert_eq!(Subnet::hosts_to_prefix(1).unwrap(), 30);
+        assert_eq!(Subnet::hosts_to_prefix(2).unwrap(), 30);
+        assert_eq!(Subnet::hosts_to_prefix(10).unwrap(), 28);
+        assert_eq!(Subnet::hosts_to_prefix(50).unwrap(), 26);
+        assert_eq!(Subnet::hosts_to_prefix(100).unwrap(), 25);
+        assert_eq!(Subnet::hosts_to_prefix(1000).unwrap(), 22);
+        assert!(matches!(
+            Subnet::hosts_to_prefix(0),
+            Err(SubnetError::ZeroHosts)
+        ));
+        assert!(matches!(
+            Subnet::hosts_to_prefix(u32::MAX),
+            Err(SubnetError::TooManyHosts { cidr: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn block_size_of_slash_26() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 50).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 26);
+        assert_eq!(subnet.interesting_octet(), 3);
+        assert_eq!(subnet.block_size(), 64);
+        assert!(format!("{:#}", subnet).contains("Block Size: 64 in the 4th octet"));
+        assert_eq!(subnet.borrowed_bits(), 2);
+        assert!(format!("{:#}", subnet).contains("Borrowed Bits: 2"));
+    }
+
+    #[test]
+    fn block_size_of_slash_18() {
+        let mut subnet = Subnet::new("10.0.0.0", 16, 16000).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 18);
+        assert_eq!(subnet.interesting_octet(), 2);
+        assert_eq!(subnet.block_size(), 64);
+    }
+
+    #[test]
+    fn interesting_octet_and_borrowed_bits_of_slash_20() {
+        let subnet = SubnetBuilder::new("172.16.0.0", 16, 4000).build().unwrap();
+        assert_eq!(subnet.next_cidr, 20);
+        assert_eq!(subnet.interesting_octet(), 2);
+        assert_eq!(subnet.block_size(), 16);
+        assert_eq!(subnet.borrowed_bits(), 4);
+    }
+
+    #[test]
+    fn borrowed_bits_without_default_mask() {
+        // A /22 in a class C network is a supernet, a multicast network has no default mask
+        let supernet = SubnetBuilder::new("192.168.0.0", 16, 1000).build().unwrap();
+        assert_eq!(supernet.borrowed_bits(), 0);
+        let multicast = SubnetBuilder::new("224.0.0.0", 24, 50).build().unwrap();
+        assert_eq!(multicast.borrowed_bits(), 0);
+    }
+
+    #[test]
+    fn class_note_of_class_c_slash_26() {
+        let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+        assert_eq!(
+            subnet.class_note().as_deref(),
+            Some("Class C network subnetted to /26 (2 bits borrowed)")
+        );
+        assert!(subnet
+            .to_string()
+            .contains("Note: Class C network subnetted to /26 (2 bits borrowed)"));
+
+        let supernet = SubnetBuilder::new("192.168.0.0", 16, 1000).build().unwrap();
+        assert_eq!(
+            supernet.class_note().as_deref(),
+            Some("Class C network supernetted to /22 (2 bits given back)")
+        );
+        let default_mask = SubnetBuilder::new("192.168.1.0", 24, 200).build().unwrap();
+        assert_eq!(default_mask.class_note(), None);
+    }
+
+    #[test]
+    fn address_scope_of_private_ranges() {
+        for network in ["10.1.0.0", "172.16.0.0", "192.168.1.0"] {
+            let subnet = SubnetBuilder::new(network, 24, 50).build().unwrap();
+            assert_eq!(subnet.address_scope(), AddressScope::Private);
+        }
+    }
+
+    #[test]
+    fn address_scope_of_other_ranges() {
+        let scope = |ip: &str| AddressScope::of(ip.parse().unwrap());
+        assert_eq!(scope("127.0.0.0"), AddressScope::Loopback);
+        assert_eq!(scope("169.254.1.0"), AddressScope::LinkLocal);
+        assert_eq!(scope("100.64.0.0"), AddressScope::Shared);
+        assert_eq!(scope("224.0.0.0"), AddressScope::Multicast);
+        assert_eq!(scope("8.8.8.0"), AddressScope::Public);
+        assert_eq!(scope("172.32.0.0"), AddressScope::Public);
+    }
+
+    #[test]
+    fn hosts_iter_of_slash_29_and_slash_30() {
+        let subnet = SubnetBuilder::new("10.0.0.0", 24, 5).build().unwrap();
+        assert_eq!(subnet.next_cidr, 29);
+        assert_eq!(subnet.hosts_iter().count(), 6);
+        assert_eq!(subnet.hosts_iter().next(), Some(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(subnet.hosts_iter().last(), Some(Ipv4Addr::new(10, 0, 0, 6)));
+        assert!(subnet.hosts_iter().any(|host| host == subnet.gateway));
+
+        let subnet = SubnetBuilder::new("10.0.0.0", 24, 2).build().unwrap();
+        assert_eq!(subnet.hosts_iter().count(), 2);
+    }
+
+    #[test]
+    fn hosts_iter_at_boundaries() {
+        let subnet = SubnetBuilder::new("255.255.255.252", 30, 2)
+            .build()
+            .unwrap();
+        assert_eq!(
+            subnet.hosts_iter().last(),
+            Some(Ipv4Addr::new(255, 255, 255, 254))
+        );
+
+        let subnet = SubnetBuilder::new("10.0.0.0", 31, 2)
+            .point_to_point(true)
+            .build()
+            .unwrap();
+        assert_eq!(subnet.hosts_iter().count(), 2);
+
+        let subnet = SubnetBuilder::new("255.255.255.255", 32, 1)
+            .point_to_point(true)
+            .build()
+            .unwrap();
+        assert_eq!(subnet.hosts_iter().count(), 0);
+
+        let uncalculated = Subnet::new("10.0.0.0", 24, 50).unwrap();
+        assert_eq!(uncalculated.hosts_iter().count(), 0);
+
+        // Counted with the size hint, iterating the whole address space would be slow
+        let everything = Subnet::describe("0.0.0.0/0").unwrap();
+        let hosts = everything.real_hosts as usize;
+        assert_eq!(everything.hosts_iter().size_hint(), (hosts, Some(hosts)));
+        assert_eq!(
+            everything.hosts_iter().next(),
+            Some(Ipv4Addr::new(0, 0, 0, 1))
+        );
+    }
+
+    #[test]
+    fn sibling_slash_25s_merge_into_slash_24() {
+        let low = SubnetBuilder::new("192.168.1.0", 25, 100).build().unwrap();
+        let high = SubnetBuilder::new("192.168.1.128", 25, 100)
+            .build()
+            .unwrap();
+        assert!(low.is_sibling_of(&high));
+        assert!(high.is_sibling_of(&low));
+
+        let merged = high.merge(&low).unwrap();
+        assert_eq!(merged.network, Ipv4Addr::new(192, 168, 1, 0));
+        assert_eq!(merged.next_cidr, 24);
+        assert_eq!(merged.broadcast, Ipv4Addr::new(192, 168, 1, 255));
+        assert_eq!(merged.hosts, 200);
+    }
+
+    #[test]
+    fn non_sibling_slash_25s_dont_merge() {
+        // Adjacent, but the halves of two different /24
+        let low = SubnetBuilder::new("192.168.1.128", 25, 100)
+            .build()
+            .unwrap();
+        let high = SubnetBuilder::new("192.168.2.0", 25, 100).build().unwrap();
+        assert!(!low.is_sibling_of(&high));
+        assert!(low.merge(&high).is_none());
+
+        let other_size = SubnetBuilder::new("192.168.1.0", 26, 50).build().unwrap();
+        assert!(!other_size.is_sibling_of(&low));
+        assert!(!low.is_sibling_of(&low));
+    }
+
+    #[test]
+    fn sibling_slash_32s_merge_into_point_to_point_slash_31() {
+        let low = SubnetBuilder::new("10.0.0.0", 32, 1)
+            .point_to_point(true)
+            .build()
+            .unwrap();
+        let high = SubnetBuilder::new("10.0.0.1", 32, 1)
+            .point_to_point(true)
+            .build()
+            .unwrap();
+
+        let merged = low.merge(&high).unwrap();
+        assert_eq!(merged.next_cidr, 31);
+        assert_eq!(merged.real_hosts, 2);
+    }
+
+    #[test]
+    fn render_custom_template() {
+        let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+        assert_eq!(
+            subnet
+                .render("{network}/{prefix} gw={gateway} hosts={usable_hosts} {{wasted: {wasted_hosts}}}")
+                .unwrap(),
+            "192.168.1.0/26 gw=192.168.1.62 hosts=62 {wasted: 14}"
+        );
+    }
+
+    #[test]
+    fn ip_with_leading_zeros_or_missing_octets() {
+        assert!(matches!(
+            Subnet::string_to_ip("010.0.0.1"),
+            Err(SubnetError::AmbiguousOctet { octet, .. }) if octet == "010"
+        ));
+        assert!(matches!(
+            Subnet::string_to_ip("192.168.1"),
+            Err(SubnetError::IncompleteIpAddress { octets: 3, .. })
+        ));
+        assert_eq!(
+            Subnet::string_to_ip("192.168.001.000").unwrap(),
+            Ipv4Addr::new(192, 168, 1, 0)
+        );
+        assert_eq!(
+            Subnet::string_to_ip("192.168.1.0").unwrap(),
+            Ipv4Addr::new(192, 168, 1, 0)
+        );
+        assert!(matches!(
+            Subnet::string_to_ip("192.168.1.256"),
+            Err(SubnetError::InvalidIpAddress(_))
+        ));
+        assert!(matches!(
+            "192.168.1/24".parse::<NetworkSpec>(),
+            Err(SubnetError::IncompleteIpAddress { .. })
+        ));
+    }
+
+    #[test]
+    fn acl_lines_of_slash_24() {
+        let subnet = Subnet::describe("10.1.1.0/24").unwrap();
+        assert_eq!(subnet.wildcard_bits(), 8);
+        assert_eq!(
+            subnet.acl_line(AclStyle::CiscoIos),
+            "access-list permit ip 10.1.1.0 0.0.0.255 any"
+        );
+        assert_eq!(
+            subnet.acl_line(AclStyle::Generic),
+            "permit ip 10.1.1.0/24 any"
+        );
+        assert_eq!(
+            subnet
+                .render("access-list 10 permit {network} {wildcard}")
+                .unwrap(),
+            "access-list 10 permit 10.1.1.0 0.0.0.255"
+        );
+    }
+
+    #[test]
+    fn render_accepts_field_names() {
+        let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+        assert_eq!(
+            subnet.render("{network}/{next_cidr} {real_hosts}").unwrap(),
+            "192.168.1.0/26 62"
+        );
+    }
+
+    #[test]
+    fn json_value_has_renamed_keys() {
+        let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+        let value = subnet.to_json_value();
+
+        assert_eq!(value["usable_hosts"], 62);
+        assert_eq!(value["next_network"], "192.168.1.64");
+        assert_eq!(value["prefix"], 26);
+        assert_eq!(value["wasted_hosts"], 14);
+        assert_eq!(value["address_scope"], "Private");
+        for key in ["real_hosts", "next_subnet", "next_cidr"] {
+            assert!(value.get(key).is_none(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn old_keys_are_still_deserialized() {
+        let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+        let json = serde_json::to_string(&subnet)
+            .unwrap()
+            .replace("usable_hosts", "real_hosts")
+            .replace("next_network", "next_subnet")
+            .replace("\"prefix\"", "\"next_cidr\"");
+
+        let old: Subnet = serde_json::from_str(&json).unwrap();
+        assert_eq!(old.real_hosts, 62);
+        assert_eq!(old.next_subnet, subnet.next_subnet);
+        assert_eq!(old.next_cidr, 26);
+    }
+
+    #[test]
+    fn render_rejects_invalid_templates() {
+        let subnet = SubnetBuilder::new("192.168.1.0", 24, 50).build().unwrap();
+        assert!(matches!(
+            subnet.render("{network} {vlan}"),
+            Err(SubnetError::InvalidTemplate(field)) if field == "{vlan}"
+        ));
+        assert!(matches!(
+            subnet.render("{network"),
+            Err(SubnetError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            subnet.render("network}"),
+            Err(SubnetError::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn cidr_to_mask_edge_cases() {
+        assert_eq!(Subnet::cidr_to_mask(0).unwrap(), Ipv4Addr::UNSPECIFIED);
+        assert_eq!(
+            Subnet::cidr_to_mask(1).unwrap(),
+            Ipv4Addr::new(128, 0, 0, 0)
+        );
+        assert_eq!(
+            Subnet::cidr_to_mask(24).unwrap(),
+            Ipv4Addr::new(255, 255, 255, 0)
+        );
+        assert_eq!(Subnet::cidr_to_mask(32).unwrap(), Ipv4Addr::BROADCAST);
+        assert!(matches!(
+            Subnet::cidr_to_mask(33),
+            Err(SubnetError::InvalidCidr(33))
+        ));
+    }
+
+    #[test]
+    fn growth_percent_rounds_up_and_sizes_the_subnet() {
+        let requirement: HostRequirement = "50+30%".parse().unwrap();
+        assert_eq!(
+            requirement,
+            HostRequirement::Growth {
+                hosts: 50,
+                grown: 65
+            }
+        );
+        assert_eq!(requirement.hosts(), 65);
+        assert_eq!(requirement.base_hosts(), Some(50));
+        assert_eq!(Subnet::hosts_to_prefix(requirement.hosts()).unwrap(), 25);
+
+        // 50 * 1.3 is 65.00000000000001 with floats
+        assert_eq!("50*1.3".parse::<HostRequirement>().unwrap().hosts(), 65);
+        assert_eq!(
+            " 10 + 12.5% ".parse::<HostRequirement>().unwrap().hosts(),
+            12
+        );
+    }
+
+    #[test]
+    fn invalid_growth_is_rejected() {
+        for input in ["50+%", "50+x%", "50*0.5", "50*", "x+30%", "4000000000*2"] {
+            assert!(
+                matches!(
+                    input.parse::<HostRequirement>(),
+                    Err(SubnetError::InvalidHosts(_))
+                ),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn subnets_sort_by_network_then_prefix() {
+        let build =
+            |network, cidr, hosts| SubnetBuilder::new(network, cidr, hosts).build().unwrap();
+        let mut subnets = vec![
+            build("192.168.1.128", 25, 10),
+            build("10.0.0.0", 8, 1000),
+            build("192.168.1.0", 24, 10),
+            build("192.168.1.0", 24, 200),
+            build("9.255.255.0", 24, 10),
+        ];
+        subnets.sort();
+
+        let sorted: Vec<String> = subnets
+            .iter()
+            .map(|s| format!("{}/{}", s.network, s.next_cidr))
+            .collect();
+        assert_eq!(
+            sorted,
+            [
+                "9.255.255.0/28",
+                "10.0.0.0/22",
+                "192.168.1.0/24",
+                "192.168.1.0/28",
+                "192.168.1.128/28"
+            ]
+        );
+
+        subnets.dedup();
+        assert_eq!(subnets.len(), 5);
+        assert_eq!(build("10.0.0.0", 24, 50), build("10.0.0.0", 24, 60));
+    }
+
+    #[test]
+    fn mask_disagreeing_with_prefix_is_a_discrepancy() {
+        let mut subnet = Subnet::describe("10.0.0.0/24").unwrap();
+        subnet.mask = Ipv4Addr::new(255, 255, 255, 192);
+        assert_eq!(
+            subnet.discrepancies(),
+            ["mask is 255.255.255.192, expected at most a /24 mask"]
+        );
+        subnet.mask = Ipv4Addr::new(255, 0, 255, 0);
+        assert_eq!(
+            subnet.discrepancies(),
+            ["mask is 255.0.255.0, expected a valid mask"]
+        );
+
+        // A mask shorter than the prefix is the one of the parent network
+        let subnet = SubnetBuilder::new("10.0.0.0", 24, 50).build().unwrap();
+        assert_eq!(subnet.mask, Ipv4Addr::new(255, 255, 255, 0));
+        assert_eq!(subnet.next_cidr, 26);
+        assert!(subnet.discrepancies().is_empty());
+    }
+
+    #[test]
+    fn mask_to_cidr_and_back() {
+        let mask = Ipv4Addr::new(255, 255, 240, 0);
+        assert_eq!(Subnet::mask_to_cidr(mask).unwrap(), 20);
+        assert_eq!(Subnet::cidr_to_mask(20).unwrap(), mask);
+
+        for cidr in 0..=IPV4_BITS {
+            let mask = Subnet::cidr_to_mask(cidr).unwrap();
+            assert_eq!(Subnet::mask_to_cidr(mask).unwrap(), cidr);
+        }
+    }
+
+    #[test]
+    fn is_valid_mask_of_several_masks() {
+        assert!(Subnet::is_valid_mask(Ipv4Addr::new(255, 255, 255, 0)));
+        assert!(!Subnet::is_valid_mask(Ipv4Addr::new(255, 255, 0, 255)));
+        assert!(Subnet::is_valid_mask(Ipv4Addr::new(0, 0, 0, 0)));
+        assert!(Subnet::is_valid_mask(Ipv4Addr::BROADCAST));
+        assert!(!Subnet::is_valid_mask(Ipv4Addr::new(255, 255, 255, 1)));
+    }
+
+    #[test]
+    fn network_spec_with_mask() {
+        let expected = NetworkSpec {
+            network: Ipv4Addr::new(192, 168, 1, 0),
+            cidr: 24,
+        };
+        assert_eq!(
+            "192.168.1.0 255.255.255.0".parse::<NetworkSpec>().unwrap(),
+            expected
+        );
+        assert_eq!(
+            "192.168.1.0/255.255.255.0".parse::<NetworkSpec>().unwrap(),
+            expected
+        );
+        assert!(matches!(
+            "192.168.1.0 255.255.0.255".parse::<NetworkSpec>(),
+            Err(SubnetError::InvalidMask(_))
+        ));
+        assert!(matches!(
+            "192.168.1.0 24".parse::<NetworkSpec>(),
+            Ok(NetworkSpec { cidr: 24, .. })
+        ));
+    }
+
+    #[test]
+    fn non_contiguous_mask_is_rejected() {
+        let error = Subnet::mask_to_cidr(Ipv4Addr::new(255, 0, 255, 0)).unwrap_err();
+        assert!(matches!(error, SubnetError::InvalidMask(_)));
+        assert_eq!(error.exit_code(), 40);
+        assert!(Subnet::mask_to_cidr(Ipv4Addr::new(0, 0, 0, 255)).is_err());
+    }
+
+    #[test]
+    fn summary_of_slash_26() {
+        let subnet = SubnetBuilder::new("10.0.0.0", 24, 50).build().unwrap();
+        assert_eq!(
+            subnet.summary(),
+            "10.0.0.0/26  hosts 62  bcast 10.0.0.63  gw 10.0.0.62"
+        );
+        assert_eq!(subnet.summary().lines().count(), 1);
+    }
+
+    #[test]
+    fn zero_hosts_is_an_error() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 0).unwrap();
+        assert!(matches!(subnet.calculate(), Err(SubnetError::ZeroHosts)));
+    }
+
+    #[test]
+    fn one_host_allocates_a_slash_30() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 1).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 30);
+        assert_eq!(subnet.real_hosts, 2);
+        assert_eq!(subnet.broadcast, Ipv4Addr::new(192, 168, 1, 3));
+    }
+
+    #[test]
+    fn overflowing_hosts_string_is_rejected() {
+        assert!(matches!(
+            Subnet::parse_hosts("5000000000"),
+            Err(SubnetError::InvalidHosts(_))
+        ));
+        assert!(matches!(
+            Subnet::parse_hosts("-5"),
+            Err(SubnetError::InvalidHosts(_))
+        ));
+        assert_eq!(Subnet::parse_hosts(" 50 ").unwrap(), 50);
+    }
+
+    #[test]
+    fn hosts_needing_prefix_shorter_than_zero_are_rejected() {
+        let mut subnet = Subnet::new("0.0.0.0", 0, u32::MAX).unwrap();
+        assert!(matches!(
+            subnet.calculate(),
+            Err(SubnetError::TooManyHosts {
+                hosts: u32::MAX,
+                cidr: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn largest_request_fills_slash_0() {
+        let mut subnet = Subnet::new("0.0.0.0", 0, u32::MAX - 1).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 0);
+        assert_eq!(subnet.total_addresses, 1 << 32);
+        assert_eq!(subnet.real_hosts, u32::MAX - 1);
+    }
+
+    #[test]
+    fn hosts_exceeding_parent_are_rejected() {
+        let mut subnet = Subnet::new("192.168.1.0", 24, 300).unwrap();
+        assert!(matches!(
+            subnet.calculate(),
+            Err(SubnetError::TooManyHosts {
+                hosts: 300,
+                cidr: 24
+            })
+        ));
+    }
+
+    #[test]
+    fn point_to_point_slash_31() {
+        let mut subnet = Subnet::new("10.0.0.4", 30, 2).unwrap();
+        subnet.point_to_point = true;
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 31);
+        assert_eq!(subnet.real_hosts, 2);
+        assert_eq!(subnet.first_host, Ipv4Addr::new(10, 0, 0, 4));
+        assert_eq!(subnet.last_host, Ipv4Addr::new(10, 0, 0, 5));
+        assert_eq!(subnet.next_subnet, Some(Ipv4Addr::new(10, 0, 0, 6)));
+    }
+
+    #[test]
+    fn single_host_slash_32() {
+        let mut subnet = Subnet::new("10.0.0.7", 32, 1).unwrap();
+        subnet.point_to_point = true;
+        subnet.calculate().unwrap();
+        assert_eq!(subnet.next_cidr, 32);
+        assert_eq!(subnet.real_hosts, 1);
+        assert_eq!(subnet.first_host, Ipv4Addr::new(10, 0, 0, 7));
+        assert_eq!(subnet.last_host, Ipv4Addr::new(10, 0, 0, 7));
+        assert_eq!(subnet.next_subnet, Some(Ipv4Addr::new(10, 0, 0, 8)));
+        assert_eq!(subnet.gateway, Ipv4Addr::new(10, 0, 0, 7));
+    }
+
+    #[test]
+    fn slash_30_gateway_stays_in_range() {
+        for policy in [GatewayPolicy::First, GatewayPolicy::Last] {
+            let subnet = SubnetBuilder::new("10.0.0.4", 30, 2)
+                .gateway_policy(policy)
+                .build()
+                .unwrap();
+            for address in [subnet.gateway, subnet.first_host, subnet.last_host] {
+                assert!(
+                    Ipv4Addr::new(10, 0, 0, 4) < address && address < Ipv4Addr::new(10, 0, 0, 7)
+                );
+            }
+            assert_ne!(subnet.gateway, subnet.first_host);
+        }
+    }
+
+    #[test]
+    fn host_outside_subnet_is_an_error() {
+        let mut subnet = SubnetBuilder::new("10.0.0.4", 30, 2).build().unwrap();
+        assert!(subnet.check_host_range().is_ok());
+
+        subnet.gateway = subnet.broadcast;
+        let error = subnet.check_host_range().unwrap_err();
+        assert!(matches!(
+            error,
+            SubnetError::HostOutsideSubnet {
+                role: "gateway",
+                prefix: 30,
+                ..
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Invalid host range: the gateway 10.0.0.7 isn't a host of 10.0.0.4/30"
+        );
     }
 }
@@ -0,0 +1,181 @@
+use std::{fmt::Display, net::Ipv6Addr};
+
+use ipnet::IpAdd;
+use serde::{Deserialize, Serialize};
+
+use crate::math::{self, IPV6_BITS};
+use crate::subnet::SubnetError;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+/**
+ * Struct that contains the IPv6 subnet information and calculated fields <br>
+ * IPv6 has no broadcast address and no address classes, so instead of the broadcast and the gateway it contains:
+ * - [`Subnet6::first_host`]: the first usable address, the network address itself is the Subnet-Router anycast address
+ * - [`Subnet6::last_address`]: the last address of the subnet, which is a regular usable address
+ *
+ * The mask and the range are computed by the same [`math::AddressFamily`] functions as the IPv4 subnets
+ */
+pub struct Subnet6 {
+    pub network: Ipv6Addr,
+    pub mask: Ipv6Addr,
+    pub cidr: u32,
+    pub first_host: Ipv6Addr,
+    pub last_address: Ipv6Addr,
+    pub next_subnet: Ipv6Addr,
+}
+
+/// Contains the IPv6 subnet information and various methods
+impl Subnet6 {
+    pub fn new(network: &str, cidr: u32) -> Result<Subnet6, SubnetError> {
+        let network = network
+            .parse()
+            .map_err(|_| SubnetError::InvalidIpAddress(network.to_string()))?;
+        let mask = math::prefix_mask(cidr).ok_or(SubnetError::InvalidCidr(cidr))?;
+
+        Ok(Subnet6 {
+            network,
+            mask,
+            cidr,
+            first_host: Ipv6Addr::UNSPECIFIED,
+            last_address: Ipv6Addr::UNSPECIFIED,
+            next_subnet: Ipv6Addr::UNSPECIFIED,
+        })
+    }
+
+    /**
+     * Calculates the following fields based on the network and the CIDR:
+     * - [`Subnet6::first_host`]
+     * - [`Subnet6::last_address`]
+     * - [`Subnet6::next_subnet`]
+     */
+    pub fn calculate(&mut self) -> Result<(), SubnetError> {
+        self.network = math::network_of(self.network, self.cidr);
+        self.last_address = math::last_address(self.network, self.cidr);
+        self.first_host = if self.cidr >= IPV6_BITS - 1 {
+            self.network
+        } else {
+            self.network.saturating_add(1)
+        };
+        self.next_subnet = self.last_address.saturating_add(1);
+
+        Ok(())
+    }
+
+    /**
+     * Splits the subnet into `count` consecutive subnets with the given CIDR <br>
+     * Returns [`SubnetError::InsufficientSpace`] if the subnets don't fit inside this subnet
+     */
+    pub fn split(&self, new_cidr: u32, count: u64) -> Result<Vec<Subnet6>, SubnetError> {
+        if new_cidr < self.cidr || new_cidr > IPV6_BITS {
+            return Err(SubnetError::InvalidCidr(new_cidr));
+        }
+
+        let available = 1u128
+            .checked_shl(new_cidr - self.cidr)
+            .map_or(u64::MAX, |n| u64::try_from(n).unwrap_or(u64::MAX));
+        if count > available {
+            return Err(SubnetError::InsufficientSpace {
+                requested: count,
+                available,
+                first_overflow: None,
+            });
+        }
+
+        let mut subnets = Vec::with_capacity(count as usize);
+        let mut network_tmp = math::network_of(self.network, self.cidr);
+
+        for _ in 0..count {
+            let mut subnet = Subnet6::new(&network_tmp.to_string(), new_cidr)?;
+            subnet.calculate()?;
+            subnets.push(subnet);
+            network_tmp = subnet.next_subnet;
+        }
+
+        Ok(subnets)
+    }
+}
+
+/// Implements the Display trait for the Subnet6 struct to print the subnet information (markdown format)
+impl Display for Subnet6 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\n## Subnet Info:\n\t - Network: {}\n\t - Mask: {}\n\t - CIDR: {}\n\t - First Host: {}\n\t - Last Address: {}",
+            self.network, self.mask, self.cidr, self.first_host, self.last_address
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calculate_slash_64() {
+        let mut subnet = Subnet6::new("2001:db8::", 64).unwrap();
+        subnet.calculate().unwrap();
+        assert_eq!(
+            subnet.mask,
+            "ffff:ffff:ffff:ffff::".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(
+            subnet.first_host,
+            "2001:db8::1".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(
+            subnet.last_address,
+            "2001:db8::ffff:ffff:ffff:ffff".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(
+            subnet.next_subnet,
+            "2001:db8:0:1::".parse::<Ipv6Addr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn split_slash_48_into_slash_64s() {
+        let mut parent = Subnet6::new("2001:db8::", 48).unwrap();
+        parent.calculate().unwrap();
+        let subnets = parent.split(64, 4).unwrap();
+
+        let networks: Vec<Ipv6Addr> = subnets.iter().map(|s| s.network).collect();
+        let expected: Vec<Ipv6Addr> = [
+            "2001:db8::",
+            "2001:db8:0:1::",
+            "2001:db8:0:2::",
+            "2001:db8:0:3::",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        assert_eq!(networks, expected);
+        assert!(subnets.iter().all(|s| s.cidr == 64));
+        assert_eq!(
+            subnets[3].last_address,
+            "2001:db8:0:3:ffff:ffff:ffff:ffff"
+                .parse::<Ipv6Addr>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn split_rejects_too_many_subnets() {
+        let parent = Subnet6::new("2001:db8::", 62).unwrap();
+        assert!(matches!(
+            parent.split(64, 5),
+            Err(SubnetError::InsufficientSpace {
+                requested: 5,
+                available: 4,
+                first_overflow: None
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_cidr_is_rejected() {
+        assert!(matches!(
+            Subnet6::new("2001:db8::", 129),
+            Err(SubnetError::InvalidCidr(129))
+        ));
+    }
+}
@@ -1,35 +1,2137 @@
-use crate::subnet::{Subnet, SubnetError};
+use std::{cmp::Reverse, collections::BTreeMap, fmt::Display, net::Ipv4Addr, str::FromStr};
+
+use crate::math::{self, IPV4_BITS, MIN_HOST_BITS};
+use crate::subnet::{
+    GatewayPolicy, HostRequirement, NetworkSpec, Subnet, SubnetBuilder, SubnetError,
+};
 
 pub struct SubnetCalculator {
     pub subnets: Vec<Subnet>,
+    /// See [`Subnet::point_to_point`]
+    pub point_to_point: bool,
+    /// See [`Subnet::gateway_policy`]
+    pub gateway_policy: GatewayPolicy,
+    /// Order and placement of the subnets in the parent network, see [`PackingStrategy`]
+    pub packing: PackingStrategy,
+    /**
+     * Prefix length of the boundary each subnet starts on (e.g. `24` to start each subnet on a /24), `None` to pack them <br>
+     * The addresses between the end of a subnet and the next boundary are intentionally left free,
+     * they're counted in [`PackingWaste::alignment_gaps`]
+     */
+    pub align: Option<u32>,
+    /// End of the parent network the subnets are allocated from, see [`FillDirection`]
+    pub fill_direction: FillDirection,
+    /**
+     * Address of the parent network the allocation starts at (e.g. `10.0.0.64` to keep the first /26 of a /24),
+     * `None` to start at the network address <br>
+     * The addresses before it are left free, it must be inside the parent network and a network address of the
+     * largest subnet (e.g. `10.0.0.64` for a /26 but not for a /25), even when that subnet could be placed further on
+     */
+    pub start_at: Option<Ipv4Addr>,
+    /// Index in the hosts array passed to [`SubnetCalculator::new`] of each calculated subnet
+    pub input_indices: Vec<usize>,
+    /// Names of the subnets in the order of the hosts array, see [`Subnet::name`], empty names leave the subnets unnamed
+    pub names: Vec<String>,
+    /// Tags of the subnets in the order of the hosts array, see [`Subnet::extra`]
+    pub extra: Vec<BTreeMap<String, String>>,
     num_hosts_array: Vec<u32>,
+    requested_prefixes: Vec<Option<u32>>,
+    base_hosts: Vec<Option<u32>>,
+    parent_network: Option<Ipv4Addr>,
+    parent_cidr: Option<u32>,
+}
+
+/**
+ * Calculates the subnets for the numbers of hosts without a [`SubnetCalculator`], see [`SubnetCalculator::calculate`] <br>
+ * The hosts aren't changed and the subnets are returned from the largest to the smallest, the allocation order
+ * ```
+ * use subnetting::calculate_vlsm;
+ *
+ * let subnets = calculate_vlsm("192.168.1.0", 24, &[20, 50]).unwrap();
+ * assert_eq!(subnets[0].network.to_string(), "192.168.1.0");
+ * assert_eq!(subnets[1].network.to_string(), "192.168.1.64");
+ * ```
+ */
+pub fn calculate_vlsm(network: &str, cidr: u32, hosts: &[u32]) -> Result<Vec<Subnet>, SubnetError> {
+    let mut calculator = SubnetCalculator::new(hosts.to_vec());
+    calculator.calculate(network, cidr)?;
+    Ok(calculator.subnets)
+}
+
+/**
+ * Finds the subnet containing the address (from its network to its broadcast address), see [`Subnet::contains`] <br>
+ * It returns the index of the subnet in the slice and the subnet, whose [`Subnet::name`] is set if it's named,
+ * or `None` if no subnet contains the address
+ * ```
+ * use subnetting::{calculate_vlsm, find_subnet};
+ *
+ * let subnets = calculate_vlsm("10.0.0.0", 24, &[50, 20]).unwrap();
+ * let (index, subnet) = find_subnet(&subnets, "10.0.0.70".parse().unwrap()).unwrap();
+ * assert_eq!((index, subnet.network.to_string()), (1, "10.0.0.64".to_string()));
+ * ```
+ */
+pub fn find_subnet(subnets: &[Subnet], ip: Ipv4Addr) -> Option<(usize, &Subnet)> {
+    subnets
+        .iter()
+        .enumerate()
+        .find(|(_, subnet)| subnet.contains(ip))
+}
+
+/**
+ * Returns the totals of the subnets (e.g. a calculated plan): the number of subnets,
+ * the usable hosts (see [`Subnet::real_hosts`]) and the allocated addresses (see [`Subnet::total_addresses`])
+ */
+pub fn plan_totals(subnets: &[Subnet]) -> PlanTotals {
+    PlanTotals {
+        subnets: subnets.len(),
+        usable_hosts: subnets.iter().map(|s| u64::from(s.real_hosts)).sum(),
+        addresses: subnets.iter().map(|s| s.total_addresses).sum(),
+    }
+}
+
+/**
+ * Aggregates a list of prefixes (e.g. from route tables) into the minimal set of prefixes covering the same addresses,
+ * the standard route aggregation <br>
+ * The prefixes covered by another one are dropped and the siblings are merged into their supernet
+ * (see [`Subnet::merge`]) until no more can be merged, the result is in network order <br>
+ * The host bits of the addresses are ignored (e.g. `10.0.0.1/24` is `10.0.0.0/24`), prefixes longer than /32 are /32
+ * ```
+ * use std::net::Ipv4Addr;
+ * use subnetting::aggregate;
+ *
+ * let prefixes = [(Ipv4Addr::new(10, 0, 0, 0), 25), (Ipv4Addr::new(10, 0, 0, 128), 25)];
+ * assert_eq!(aggregate(&prefixes), vec![(Ipv4Addr::new(10, 0, 0, 0), 24)]);
+ * ```
+ */
+pub fn aggregate(prefixes: &[(Ipv4Addr, u32)]) -> Vec<(Ipv4Addr, u32)> {
+    let mut sorted: Vec<(Ipv4Addr, u32)> = prefixes
+        .iter()
+        .map(|&(ip, cidr)| {
+            let cidr = cidr.min(IPV4_BITS);
+            (math::network(ip, cidr), cidr)
+        })
+        .collect();
+    sorted.sort();
+
+    let mut aggregated: Vec<(Ipv4Addr, u32)> = Vec::with_capacity(sorted.len());
+    for (network, cidr) in sorted {
+        // The prefixes are sorted by network and then from the largest, so a covering prefix is the last one kept
+        if let Some(&(last, last_cidr)) = aggregated.last() {
+            if last_cidr <= cidr && math::network(network, last_cidr) == last {
+                continue;
+            }
+        }
+
+        aggregated.push((network, cidr));
+        while let [.., (low, low_cidr), (high, high_cidr)] = aggregated[..] {
+            if low_cidr != high_cidr || !math::are_siblings(low, high, high_cidr) {
+                break;
+            }
+            aggregated.truncate(aggregated.len() - 2);
+            aggregated.push((low, low_cidr - 1));
+        }
+    }
+    aggregated
+}
+
+/**
+ * Checks that the subnets of a plan (e.g. loaded from a saved file) are consistent without recalculating them <br>
+ * The fields of each subnet are compared with the ones recomputed from its network and prefix length
+ * (see [`Subnet::discrepancies`]), the subnets mustn't overlap and must be inside the parent network, if any <br>
+ * It returns the index of the subnet and the description of each discrepancy, empty if the plan is consistent
+ */
+pub fn check_plan(subnets: &[Subnet], parent: Option<NetworkSpec>) -> Vec<(usize, String)> {
+    let mut discrepancies: Vec<(usize, String)> = subnets
+        .iter()
+        .enumerate()
+        .flat_map(|(i, subnet)| subnet.discrepancies().into_iter().map(move |d| (i, d)))
+        .collect();
+
+    for (i, j) in overlapping_pairs(subnets) {
+        discrepancies.push((i, format!("overlaps subnet #{}", j + 1)));
+    }
+
+    if let Some(parent) = parent {
+        let parent_network = math::network(parent.network, parent.cidr);
+        let parent_broadcast = math::broadcast(parent.network, parent.cidr);
+        for (i, subnet) in subnets.iter().enumerate() {
+            if subnet.network < parent_network || subnet.broadcast > parent_broadcast {
+                discrepancies.push((i, format!("isn't inside the parent network {}", parent)));
+            }
+        }
+    }
+
+    discrepancies.sort_by_key(|&(i, _)| i);
+    discrepancies
+}
+
+/// Helper function to get the pairs of indexes of the overlapping subnets, see [`SubnetCalculator::find_overlaps`]
+fn overlapping_pairs(subnets: &[Subnet]) -> Vec<(usize, usize)> {
+    let mut overlaps = Vec::new();
+    for (i, a) in subnets.iter().enumerate() {
+        for (j, b) in subnets.iter().enumerate().skip(i + 1) {
+            if a.network <= b.broadcast && b.network <= a.broadcast {
+                overlaps.push((i, j));
+            }
+        }
+    }
+    overlaps
+}
+
+/**
+ * Free address ranges of a pool, used to place the subnets in the first gap they fit in
+ * see [`SubnetCalculator::calculate_in_pool`] <br>
+ * The ranges are sorted, with the start included and the end excluded, as `u64` to hold the end of 255.255.255.255 <br>
+ * If `top_down`, the gaps are searched from the end of the pool and the blocks are placed at the end of the gaps
+ */
+struct FreeSpace {
+    gaps: Vec<(u64, u64)>,
+    top_down: bool,
+}
+
+impl FreeSpace {
+    fn new(start: u64, end: u64, top_down: bool) -> FreeSpace {
+        FreeSpace {
+            gaps: vec![(start, end)],
+            top_down,
+        }
+    }
+
+    /// Removes the range from the free space, the parts outside of the pool are ignored
+    fn reserve(&mut self, start: u64, end: u64) {
+        self.gaps = self
+            .gaps
+            .iter()
+            .flat_map(|&(gap_start, gap_end)| {
+                [
+                    (gap_start, start.min(gap_end)),
+                    (end.max(gap_start), gap_end),
+                ]
+            })
+            .filter(|(gap_start, gap_end)| gap_start < gap_end)
+            .collect();
+    }
+
+    /**
+     * Returns the start of the block of the given size aligned to its size in the gap, if it fits <br>
+     * The block is at the start of the gap, or at its end if `top_down`
+     */
+    fn fit(&self, (gap_start, gap_end): (u64, u64), size: u64) -> Option<u64> {
+        if self.top_down {
+            // The predecessor network of the end of the gap, e.g. 10.0.0.192 for a /26 ending at 10.0.1.0
+            let start = gap_end.checked_sub(size)? / size * size;
+            (start >= gap_start).then_some(start)
+        } else {
+            let start = gap_start.next_multiple_of(size);
+            (start + size <= gap_end).then_some(start)
+        }
+    }
+
+    /// Helper function to get the gaps in the order they're searched, from the end of the pool if `top_down`
+    fn ordered_gaps(&self) -> Vec<(u64, u64)> {
+        if self.top_down {
+            self.gaps.iter().rev().copied().collect()
+        } else {
+            self.gaps.clone()
+        }
+    }
+
+    /// Reserves the first block of the given size aligned to its size and returns its start
+    fn allocate(&mut self, size: u64) -> Option<u64> {
+        let start = self
+            .ordered_gaps()
+            .into_iter()
+            .find_map(|gap| self.fit(gap, size))?;
+        self.reserve(start, start + size);
+        Some(start)
+    }
+
+    /// Reserves a block of the given size aligned to its size in the smallest gap it fits in and returns its start
+    fn allocate_best(&mut self, size: u64) -> Option<u64> {
+        let (_, start) = self
+            .ordered_gaps()
+            .into_iter()
+            .filter_map(|gap| Some((gap.1 - gap.0, self.fit(gap, size)?)))
+            .min_by_key(|&(gap_size, _)| gap_size)?;
+        self.reserve(start, start + size);
+        Some(start)
+    }
+
+    /// Returns the size of the largest gap
+    fn largest_gap(&self) -> u64 {
+        self.gaps
+            .iter()
+            .map(|(start, end)| end - start)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Prefix chosen for a number of hosts, see [`SubnetCalculator::preview`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefixPreview {
+    /// Requested number of hosts
+    pub hosts: u32,
+    /// Prefix length of the subnet
+    pub prefix: u32,
+    /// Number of usable hosts of the subnet
+    pub usable: u32,
+    /// Usable hosts that aren't requested
+    pub wasted: u32,
+}
+
+/// Address space utilization of the parent network after the allocation, see [`SubnetCalculator::utilization`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Utilization {
+    /// Number of addresses of the parent network
+    pub total: u64,
+    /// Number of addresses of the allocated subnets
+    pub allocated: u64,
+    /// Number of usable hosts of the allocated subnets
+    pub usable: u64,
+    /// Percentage of the allocated addresses used by the requested hosts
+    pub efficiency_percent: f64,
+}
+
+/// Totals of a list of subnets printed after the results, see [`plan_totals`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanTotals {
+    /// Number of subnets
+    pub subnets: usize,
+    /// Number of usable hosts of the subnets
+    pub usable_hosts: u64,
+    /// Number of addresses of the subnets, including the network and broadcast addresses
+    pub addresses: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Order and placement of the subnets in the parent network, see [`SubnetCalculator::packing`]
+pub enum PackingStrategy {
+    /// From the largest to the smallest subnet, one after the other from the start of the network
+    #[default]
+    LargestFirst,
+    /// From the smallest to the largest subnet, each aligned to its size after the previous ones
+    SmallestFirst,
+    /// In the order of the hosts array, each in the smallest free gap it fits in (aligned to its size)
+    BestFit,
+}
+
+impl PackingStrategy {
+    /// Every strategy, in the order of [`SubnetCalculator::compare_packing`]
+    pub const ALL: [PackingStrategy; 3] = [
+        PackingStrategy::LargestFirst,
+        PackingStrategy::SmallestFirst,
+        PackingStrategy::BestFit,
+    ];
+}
+
+/// Parses a packing strategy from `largest-first`, `smallest-first` or `best-fit` (case insensitive)
+impl FromStr for PackingStrategy {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "largest-first" => Ok(PackingStrategy::LargestFirst),
+            "smallest-first" => Ok(PackingStrategy::SmallestFirst),
+            "best-fit" => Ok(PackingStrategy::BestFit),
+            _ => Err(SubnetError::InvalidPackingStrategy(s.to_string())),
+        }
+    }
+}
+
+/// Prints the packing strategy as it's parsed (e.g. `best-fit`)
+impl Display for PackingStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PackingStrategy::LargestFirst => "largest-first",
+            PackingStrategy::SmallestFirst => "smallest-first",
+            PackingStrategy::BestFit => "best-fit",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// End of the parent network the subnets are allocated from, see [`SubnetCalculator::fill_direction`]
+pub enum FillDirection {
+    /// From the start of the network upwards, the first subnet at the network address
+    #[default]
+    BottomUp,
+    /// From the end of the network downwards, the first subnet ending at the broadcast address
+    TopDown,
+}
+
+/// Parses a fill direction from `bottom-up` or `top-down` (case insensitive)
+impl FromStr for FillDirection {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "bottom-up" => Ok(FillDirection::BottomUp),
+            "top-down" => Ok(FillDirection::TopDown),
+            _ => Err(SubnetError::InvalidFillDirection(s.to_string())),
+        }
+    }
+}
+
+/// Addresses wasted by the allocation of the subnets, see [`SubnetCalculator::packing_waste`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackingWaste {
+    /// Addresses of the subnets that aren't used by the requested hosts, see [`Subnet::wasted_hosts`]
+    pub wasted_hosts: u64,
+    /// Free addresses left between the subnets to align them, from the first to the last subnet
+    pub alignment_gaps: u64,
+}
+
+impl PackingWaste {
+    /// Returns the wasted addresses, in the subnets and between them
+    pub fn total(&self) -> u64 {
+        self.wasted_hosts + self.alignment_gaps
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Order of the calculated subnets in the output, see [`SubnetCalculator::sorted_positions`]
+pub enum SortOrder {
+    /// Order of the hosts array passed to [`SubnetCalculator::new`]
+    Input,
+    /// Ascending network address
+    Network,
+    /// From the largest to the smallest subnet, the order in which they're allocated
+    #[default]
+    Size,
+}
+
+/// Parses a sort order from `input`, `network` or `size` (case insensitive)
+impl FromStr for SortOrder {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "input" => Ok(SortOrder::Input),
+            "network" => Ok(SortOrder::Network),
+            "size" => Ok(SortOrder::Size),
+            _ => Err(SubnetError::InvalidSortOrder(s.to_string())),
+        }
+    }
 }
 
 impl SubnetCalculator {
     pub fn new(num_hosts_array: Vec<u32>) -> SubnetCalculator {
         SubnetCalculator {
             subnets: Vec::new(),
+            point_to_point: false,
+            gateway_policy: GatewayPolicy::default(),
+            packing: PackingStrategy::default(),
+            align: None,
+            fill_direction: FillDirection::default(),
+            start_at: None,
+            input_indices: Vec::new(),
+            names: Vec::new(),
+            extra: Vec::new(),
+            requested_prefixes: vec![None; num_hosts_array.len()],
+            base_hosts: vec![None; num_hosts_array.len()],
             num_hosts_array,
+            parent_network: None,
+            parent_cidr: None,
         }
     }
 
-    /// Calculates the subnet for each number of hosts in the array
+    /**
+     * Creates a calculator from host requirements, which can be numbers of hosts or prefix lengths <br>
+     * The subnets requested by prefix keep it in [`Subnet::requested_prefix`]
+     * and the ones with a growth headroom keep the hosts before the growth in [`Subnet::requested_hosts`]
+     */
+    pub fn from_requirements(requirements: &[HostRequirement]) -> SubnetCalculator {
+        let mut calculator =
+            SubnetCalculator::new(requirements.iter().map(|r| r.hosts()).collect());
+        calculator.requested_prefixes = requirements.iter().map(|r| r.prefix()).collect();
+        calculator.base_hosts = requirements.iter().map(|r| r.base_hosts()).collect();
+        calculator
+    }
+
+    /**
+     * Calculates the subnet for each number of hosts in the array, by default from the largest to the smallest <br>
+     * The other strategies of [`SubnetCalculator::packing`] align each subnet to its size in the free space left <br>
+     * With [`SubnetCalculator::align`] each subnet starts on the next boundary instead of right after the previous one <br>
+     * With [`SubnetCalculator::start_at`] the allocation starts at that address instead of the network address <br>
+     * With [`FillDirection::TopDown`] the first subnet is placed at the end of the network and each next subnet
+     * right below the previous one (its predecessor network), with any strategy <br>
+     * Returns [`SubnetError::InsufficientSpace`] if the subnets don't fit inside the parent network
+     */
     pub fn calculate(&mut self, network: &str, cidr: u32) -> Result<(), SubnetError> {
-        self.num_hosts_array.sort_by(|a, b| b.cmp(a));
+        let mut indices = self.prepare(network, cidr)?;
 
-        let mut network_tmp = network.to_string();
-        let mut cidr_tmp = cidr;
+        let subnets = match self.packing {
+            PackingStrategy::LargestFirst if self.fill_direction == FillDirection::TopDown => {
+                self.place(cidr, &indices, &mut self.free_pool(cidr)?, false)?
+            }
+            PackingStrategy::LargestFirst => {
+                let mut subnets = Vec::with_capacity(self.num_hosts_array.len());
+                let allocation =
+                    self.allocate(network, cidr, &indices, |_, subnet| subnets.push(subnet))?;
+                SubnetCalculator::check_space(allocation, cidr)?;
+                subnets
+            }
+            PackingStrategy::SmallestFirst => {
+                indices.sort_by_key(|&index| self.num_hosts_array[index]);
+                self.place(cidr, &indices, &mut self.free_pool(cidr)?, false)?
+            }
+            PackingStrategy::BestFit => {
+                indices.sort();
+                self.place(cidr, &indices, &mut self.free_pool(cidr)?, true)?
+            }
+        };
+
+        self.subnets.extend(subnets);
+        self.input_indices.extend(indices);
+
+        Ok(())
+    }
+
+    /**
+     * Returns the waste of each [`PackingStrategy`] for the hosts in the network, without changing the calculator <br>
+     * The strategies whose subnets don't fit return the error of [`SubnetCalculator::calculate`]
+     */
+    pub fn compare_packing(
+        &self,
+        network: &str,
+        cidr: u32,
+    ) -> Vec<(PackingStrategy, Result<PackingWaste, SubnetError>)> {
+        PackingStrategy::ALL
+            .into_iter()
+            .map(|packing| {
+                let mut calculator = SubnetCalculator::from_requirements(&self.requirements());
+                calculator.point_to_point = self.point_to_point;
+                calculator.gateway_policy = self.gateway_policy;
+                calculator.packing = packing;
+                calculator.align = self.align;
+                calculator.fill_direction = self.fill_direction;
+                calculator.start_at = self.start_at;
+                let waste = calculator
+                    .calculate(network, cidr)
+                    .map(|()| calculator.packing_waste());
+                (packing, waste)
+            })
+            .collect()
+    }
 
-        for num_hosts in self.num_hosts_array.iter() {
-            let mut subnet = Subnet::new(&network_tmp, cidr_tmp, *num_hosts)?;
-            subnet.calculate()?;
-            self.subnets.push(subnet);
+    /**
+     * Returns the addresses wasted by the calculated subnets, in the subnets and in the alignment gaps between them <br>
+     * The free space before the first and after the last subnet isn't counted, it's still available for other subnets
+     */
+    pub fn packing_waste(&self) -> PackingWaste {
+        let wasted_hosts = self
+            .subnets
+            .iter()
+            .map(|subnet| u64::from(subnet.wasted_hosts()))
+            .sum();
+        let allocated: u64 = self.subnets.iter().map(|s| s.total_addresses).sum();
+        let start = self
+            .subnets
+            .iter()
+            .map(|subnet| u64::from(u32::from(subnet.network)))
+            .min()
+            .unwrap_or(0);
+        let end = self
+            .subnets
+            .iter()
+            .map(|subnet| u64::from(u32::from(subnet.broadcast)) + 1)
+            .max()
+            .unwrap_or(start);
 
-            let next_network_tmp = subnet.next_subnet.to_string();
-            network_tmp = next_network_tmp;
+        PackingWaste {
+            wasted_hosts,
+            alignment_gaps: (end - start).saturating_sub(allocated),
+        }
+    }
+
+    /**
+     * Calculates the same subnets as [`SubnetCalculator::calculate`] with the default [`PackingStrategy::LargestFirst`]
+     * and [`FillDirection::BottomUp`], in the same order, but passes each of them to `on_subnet`
+     * with its index in the hosts array instead of keeping them in [`SubnetCalculator::subnets`] <br>
+     * It's the only placement that doesn't track the free space, so the other strategies and [`FillDirection::TopDown`]
+     * return [`SubnetError::UnsupportedStreaming`] <br>
+     * It's meant for very large allocations whose output is written incrementally <br>
+     * The subnets are allocated twice, the first time only to check that they fit,
+     * so `on_subnet` isn't called at all if an error is returned
+     */
+    pub fn calculate_streaming<F>(
+        &mut self,
+        network: &str,
+        cidr: u32,
+        on_subnet: F,
+    ) -> Result<(), SubnetError>
+    where
+        F: FnMut(usize, Subnet),
+    {
+        if self.packing != PackingStrategy::LargestFirst {
+            return Err(SubnetError::UnsupportedStreaming(self.packing.to_string()));
+        }
+        if self.fill_direction == FillDirection::TopDown {
+            return Err(SubnetError::UnsupportedStreaming("top-down".to_string()));
+        }
+        let indices = self.prepare(network, cidr)?;
+
+        let allocation = self.allocate(network, cidr, &indices, |_, _| {})?;
+        SubnetCalculator::check_space(allocation, cidr)?;
+        self.allocate(network, cidr, &indices, on_subnet)?;
+
+        Ok(())
+    }
+
+    /**
+     * Calculates the subnets in the free space of a pool, skipping the subnets already allocated in it <br>
+     * The subnets are placed from the largest to the smallest, each in the first gap it fits in (first fit),
+     * instead of one after the other from the start of the network like [`SubnetCalculator::calculate`] <br>
+     * Returns [`SubnetError::InsufficientSpace`] with the largest gap if a subnet doesn't fit in any gap
+     */
+    pub fn calculate_in_pool(
+        &mut self,
+        network: &str,
+        cidr: u32,
+        allocated: &[Subnet],
+    ) -> Result<(), SubnetError> {
+        let indices = self.prepare(network, cidr)?;
+
+        let mut free_space = self.free_pool(cidr)?;
+        for subnet in allocated {
+            free_space.reserve(
+                u32::from(subnet.network).into(),
+                u64::from(u32::from(subnet.broadcast)) + 1,
+            );
+        }
+        let subnets = self.place(cidr, &indices, &mut free_space, false)?;
+
+        self.subnets.extend(subnets);
+        self.input_indices.extend(indices);
+
+        Ok(())
+    }
+
+    /**
+     * Merges a saved plan with the new host requirements of the calculator <br>
+     * The subnets of the plan keep their placement, and the new subnets are allocated in the free gaps of the network
+     * around them, see [`SubnetCalculator::calculate_in_pool`] <br>
+     * It returns the subnets of the plan, unchanged, and the new subnets in network order,
+     * the new subnets alone are kept in [`SubnetCalculator::subnets`]
+     */
+    pub fn merge_plan(
+        &mut self,
+        network: &str,
+        cidr: u32,
+        plan: &[Subnet],
+    ) -> Result<Vec<Subnet>, SubnetError> {
+        self.calculate_in_pool(network, cidr, plan)?;
+
+        let mut merged: Vec<Subnet> = plan.iter().chain(&self.subnets).cloned().collect();
+        merged.sort();
+        Ok(merged)
+    }
+
+    /**
+     * Helper function to get the free space of the parent network kept by [`SubnetCalculator::prepare`] <br>
+     * Returns [`SubnetError::NotANetworkAddress`] if the network isn't aligned to the CIDR
+     */
+    fn free_pool(&self, cidr: u32) -> Result<FreeSpace, SubnetError> {
+        let pool = self.parent_network.map_or(0, u32::from);
+        let pool_size = SubnetCalculator::block_size(cidr);
+        if u64::from(pool) % pool_size != 0 {
+            return Err(SubnetError::NotANetworkAddress {
+                address: Ipv4Addr::from(pool),
+                network: Ipv4Addr::from(pool & !((pool_size - 1) as u32)),
+                cidr,
+            });
+        }
+        let mut free_space = FreeSpace::new(
+            pool.into(),
+            u64::from(pool) + pool_size,
+            self.fill_direction == FillDirection::TopDown,
+        );
+        free_space.reserve(pool.into(), u64::from(pool) + self.start_offset());
+        Ok(free_space)
+    }
+
+    /**
+     * Helper function to place the subnets in the given order in the free space, each aligned to its size
+     * in the first gap it fits in, or in the smallest one if `best_fit` <br>
+     * Returns [`SubnetError::InsufficientSpace`] with the largest gap if a subnet doesn't fit in any gap
+     */
+    fn place(
+        &self,
+        cidr: u32,
+        indices: &[usize],
+        free_space: &mut FreeSpace,
+        best_fit: bool,
+    ) -> Result<Vec<Subnet>, SubnetError> {
+        let previews = self.preview()?;
+        let mut subnets = Vec::with_capacity(indices.len());
+        for &index in indices {
+            let prefix = previews[index].prefix;
+            if prefix < cidr {
+                return Err(SubnetError::TooManyHosts {
+                    hosts: self.num_hosts_array[index],
+                    cidr,
+                });
+            }
+
+            // A subnet smaller than the boundary is padded to it, so the next subnet starts on the next boundary
+            let size = SubnetCalculator::block_size(prefix)
+                .max(self.align.map_or(0, SubnetCalculator::block_size));
+            let start = if best_fit {
+                free_space.allocate_best(size)
+            } else {
+                free_space.allocate(size)
+            };
+            let start = start.ok_or(SubnetError::InsufficientSpace {
+                requested: size,
+                available: free_space.largest_gap(),
+                first_overflow: Some(index),
+            })?;
+            let network = Ipv4Addr::from(start as u32).to_string();
+            let mut subnet = SubnetBuilder::new(&network, prefix, self.num_hosts_array[index])
+                .point_to_point(self.point_to_point)
+                .gateway_policy(self.gateway_policy)
+                .build()?;
+            subnet.requested_prefix = self.requested_prefixes[index];
+            subnet.requested_hosts = self.base_hosts[index];
+            subnet.name = self.name(index);
+            subnet.extra = self.tags(index);
+            subnets.push(subnet);
+        }
+        Ok(subnets)
+    }
+
+    /**
+     * Helper function to validate the parent network and keep it, see [`SubnetCalculator::parent_network`] <br>
+     * It returns the indices of the hosts array from the largest to the smallest number of hosts, the allocation order
+     */
+    fn prepare(&mut self, network: &str, cidr: u32) -> Result<Vec<usize>, SubnetError> {
+        if cidr > IPV4_BITS {
+            return Err(SubnetError::InvalidCidr(cidr));
+        }
+        if let Some(align) = self.align.filter(|&align| align > IPV4_BITS) {
+            return Err(SubnetError::InvalidCidr(align));
+        }
+
+        let parent_network = network
+            .parse()
+            .map_err(|_| SubnetError::InvalidIpAddress(network.to_string()))?;
+        if let Some(start) = self.start_at {
+            if math::network(start, cidr) != math::network(parent_network, cidr)
+                || start < parent_network
+            {
+                return Err(SubnetError::HostOutsideSubnet {
+                    role: "start address",
+                    address: start,
+                    network: parent_network,
+                    prefix: cidr,
+                });
+            }
+        }
+        self.parent_network = Some(parent_network);
+        self.parent_cidr = Some(cidr);
+
+        let mut indices: Vec<usize> = (0..self.num_hosts_array.len()).collect();
+        indices.sort_by(|&a, &b| self.num_hosts_array[b].cmp(&self.num_hosts_array[a]));
+
+        // Whatever the strategy, the start must be aligned to the largest subnet, the first one placed by default
+        if let (Some(start), Some(&largest)) = (self.start_at, indices.first()) {
+            let prefix = self.preview()?[largest].prefix;
+            if prefix >= cidr && math::network(start, prefix) != start {
+                return Err(SubnetError::UnalignedStart {
+                    address: start,
+                    prefix,
+                });
+            }
+        }
+        Ok(indices)
+    }
+
+    /**
+     * Helper function to allocate the subnets one after the other in the given order, starting from the network <br>
+     * Each subnet is passed to `on_subnet` with its index, it returns the number of allocated addresses
+     * and the index of the first subnet ending outside the parent network, if any
+     */
+    fn allocate<F>(
+        &self,
+        network: &str,
+        cidr: u32,
+        indices: &[usize],
+        mut on_subnet: F,
+    ) -> Result<(u64, Option<usize>), SubnetError>
+    where
+        F: FnMut(usize, Subnet),
+    {
+        let mut network_tmp = Some(self.start_at.map_or(network.to_string(), |s| s.to_string()));
+        // A subnet starting after the network address is at most the largest block aligned at its start
+        let mut cidr_tmp = self.start_at.map_or(cidr, |start| {
+            (IPV4_BITS - u32::from(start).trailing_zeros()).max(cidr)
+        });
+        let mut requested = self.start_offset();
+        let mut first_overflow = None;
+        let available = SubnetCalculator::block_size(cidr);
+
+        for &index in indices {
+            // There's no next subnet after a subnet ending at 255.255.255.255
+            let network = network_tmp
+                .take()
+                .ok_or(SubnetError::AddressSpaceExhausted)?;
+            let (network, gap) = self.align_network(&network)?;
+            requested += gap;
+            let mut subnet = SubnetBuilder::new(&network, cidr_tmp, self.num_hosts_array[index])
+                .point_to_point(self.point_to_point)
+                .gateway_policy(self.gateway_policy)
+                .build()?;
+            subnet.requested_prefix = self.requested_prefixes[index];
+            subnet.requested_hosts = self.base_hosts[index];
+            subnet.name = self.name(index);
+            subnet.extra = self.tags(index);
+            requested += SubnetCalculator::block_size(subnet.next_cidr);
+            if requested > available && first_overflow.is_none() {
+                first_overflow = Some(index);
+            }
+
+            network_tmp = subnet.next_subnet.map(|next| next.to_string());
             cidr_tmp = subnet.next_cidr;
+            on_subnet(index, subnet);
         }
 
+        Ok((requested, first_overflow))
+    }
+
+    /**
+     * Helper function to round the network up to the next boundary of [`SubnetCalculator::align`], if any <br>
+     * It returns the aligned network and the number of addresses skipped to reach it
+     */
+    fn align_network(&self, network: &str) -> Result<(String, u64), SubnetError> {
+        let Some(align) = self.align else {
+            return Ok((network.to_string(), 0));
+        };
+        let start =
+            u64::from(u32::from(network.parse::<Ipv4Addr>().map_err(|_| {
+                SubnetError::InvalidIpAddress(network.to_string())
+            })?));
+        let boundary = SubnetCalculator::block_size(align);
+        let aligned = start.div_ceil(boundary) * boundary;
+        let aligned = u32::try_from(aligned).map_err(|_| SubnetError::AddressSpaceExhausted)?;
+        Ok((
+            Ipv4Addr::from(aligned).to_string(),
+            u64::from(aligned) - start,
+        ))
+    }
+
+    /// Helper function to get the number of addresses skipped before [`SubnetCalculator::start_at`], if any
+    fn start_offset(&self) -> u64 {
+        match (self.start_at, self.parent_network) {
+            (Some(start), Some(network)) => u64::from(u32::from(start) - u32::from(network)),
+            _ => 0,
+        }
+    }
+
+    /// Helper function to get the tags of the subnet at the index of the hosts array, if any
+    fn tags(&self, index: usize) -> BTreeMap<String, String> {
+        self.extra.get(index).cloned().unwrap_or_default()
+    }
+
+    /// Helper function to get the name of the subnet at the index of the hosts array, if any
+    fn name(&self, index: usize) -> Option<String> {
+        self.names
+            .get(index)
+            .filter(|name| !name.is_empty())
+            .cloned()
+    }
+
+    /**
+     * Helper function to return [`SubnetError::InsufficientSpace`] if the allocated addresses exceed the parent network <br>
+     * It takes the result of [`SubnetCalculator::allocate`], the error names the first subnet that doesn't fit
+     */
+    fn check_space(
+        (requested, first_overflow): (u64, Option<usize>),
+        cidr: u32,
+    ) -> Result<(), SubnetError> {
+        match first_overflow {
+            Some(index) => Err(SubnetError::InsufficientSpace {
+                requested,
+                available: SubnetCalculator::block_size(cidr),
+                first_overflow: Some(index),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the host requirements passed to the calculator, in input order
+    pub fn requirements(&self) -> Vec<HostRequirement> {
+        self.num_hosts_array
+            .iter()
+            .zip(self.requested_prefixes.iter().zip(&self.base_hosts))
+            .map(|(&grown, requested)| match requested {
+                (Some(prefix), _) => HostRequirement::Prefix(*prefix),
+                (None, Some(hosts)) => HostRequirement::Growth {
+                    hosts: *hosts,
+                    grown,
+                },
+                (None, None) => HostRequirement::Hosts(grown),
+            })
+            .collect()
+    }
+
+    /**
+     * Replaces the host requirements and calculates the subnets again in the same parent network <br>
+     * The calculator is left unchanged if the new subnets don't fit,
+     * if no network has been calculated yet only the requirements are replaced
+     */
+    pub fn recalculate(&mut self, requirements: &[HostRequirement]) -> Result<(), SubnetError> {
+        let mut calculator = SubnetCalculator::from_requirements(requirements);
+        calculator.point_to_point = self.point_to_point;
+        calculator.gateway_policy = self.gateway_policy;
+        calculator.packing = self.packing;
+        calculator.align = self.align;
+        calculator.fill_direction = self.fill_direction;
+        calculator.start_at = self.start_at;
+        calculator.names = self.names.clone();
+        calculator.extra = self.extra.clone();
+
+        if let (Some(network), Some(cidr)) = (self.parent_network, self.parent_cidr) {
+            calculator.calculate(&network.to_string(), cidr)?;
+        }
+
+        *self = calculator;
         Ok(())
     }
+
+    /// Returns the network passed to [`SubnetCalculator::calculate`], or `None` if no network has been calculated yet
+    pub fn parent_network(&self) -> Option<Ipv4Addr> {
+        self.parent_network
+    }
+
+    /// Returns the CIDR passed to [`SubnetCalculator::calculate`], or `None` if no network has been calculated yet
+    pub fn parent_cidr(&self) -> Option<u32> {
+        self.parent_cidr
+    }
+
+    /**
+     * Returns the prefix of each requested number of hosts, in input order, without allocating the subnets <br>
+     * The sizes are the same as [`SubnetCalculator::calculate`], see [`Subnet::hosts_to_prefix`]
+     */
+    pub fn preview(&self) -> Result<Vec<PrefixPreview>, SubnetError> {
+        self.num_hosts_array
+            .iter()
+            .map(|&hosts| {
+                let (prefix, usable) = if self.point_to_point && hosts <= 2 {
+                    (IPV4_BITS + 1 - hosts, hosts)
+                } else {
+                    let prefix = Subnet::hosts_to_prefix(hosts)?;
+                    (prefix, ((1u64 << (IPV4_BITS - prefix)) - 2) as u32)
+                };
+                Ok(PrefixPreview {
+                    hosts,
+                    prefix,
+                    usable,
+                    wasted: usable.saturating_sub(hosts),
+                })
+            })
+            .collect()
+    }
+
+    /**
+     * Splits the network into `num_subnets` subnets of equal size (FLSM) <br>
+     * The smallest prefix able to produce at least `num_subnets` blocks is used,
+     * so the returned subnets are as large as possible <br>
+     * Returns [`SubnetError::ZeroSubnets`] for 0 subnets
+     * and [`SubnetError::TooManySubnets`] if the subnets don't fit inside the parent network
+     */
+    pub fn split_equal(
+        network: &str,
+        cidr: u32,
+        num_subnets: u32,
+    ) -> Result<Vec<Subnet>, SubnetError> {
+        if cidr > IPV4_BITS {
+            return Err(SubnetError::InvalidCidr(cidr));
+        }
+        if num_subnets == 0 {
+            return Err(SubnetError::ZeroSubnets);
+        }
+
+        // Each subnet needs at least a /30 to have usable hosts
+        let available = SubnetCalculator::block_size(cidr) >> MIN_HOST_BITS;
+        let new_cidr = match num_subnets.checked_next_power_of_two() {
+            Some(n) if u64::from(n) <= available => cidr + n.trailing_zeros(),
+            _ => {
+                return Err(SubnetError::TooManySubnets {
+                    requested: num_subnets.into(),
+                    available,
+                })
+            }
+        };
+
+        let hosts = math::usable_hosts(new_cidr);
+        let mut network_tmp = Some(network.to_string());
+        let mut cidr_tmp = cidr;
+        let mut subnets = Vec::with_capacity(num_subnets as usize);
+
+        for _ in 0..num_subnets {
+            let network = network_tmp
+                .take()
+                .ok_or(SubnetError::AddressSpaceExhausted)?;
+            let subnet = SubnetBuilder::new(&network, cidr_tmp, hosts).build()?;
+            network_tmp = subnet.next_subnet.map(|next| next.to_string());
+            cidr_tmp = subnet.next_cidr;
+            subnets.push(subnet);
+        }
+
+        Ok(subnets)
+    }
+
+    /**
+     * Splits the network into `count` consecutive point-to-point links, /30 subnets or /31 subnets (RFC 3021) if `use_31` <br>
+     * Returns [`SubnetError::TooManySubnets`] if the links don't fit inside the parent network
+     */
+    pub fn split_ptp(
+        network: &str,
+        cidr: u32,
+        count: u32,
+        use_31: bool,
+    ) -> Result<Vec<Subnet>, SubnetError> {
+        if cidr > IPV4_BITS {
+            return Err(SubnetError::InvalidCidr(cidr));
+        }
+
+        let link_bits = if use_31 { 1 } else { MIN_HOST_BITS };
+        let available = SubnetCalculator::block_size(cidr) >> link_bits;
+        if u64::from(count) > available {
+            return Err(SubnetError::TooManySubnets {
+                requested: count.into(),
+                available,
+            });
+        }
+
+        let mut network_tmp = Some(network.to_string());
+        let mut cidr_tmp = cidr;
+        let mut links = Vec::with_capacity(count as usize);
+
+        for _ in 0..count {
+            let network = network_tmp
+                .take()
+                .ok_or(SubnetError::AddressSpaceExhausted)?;
+            let link = SubnetBuilder::new(&network, cidr_tmp, 2)
+                .point_to_point(use_31)
+                .build()?;
+            network_tmp = link.next_subnet.map(|next| next.to_string());
+            cidr_tmp = link.next_cidr;
+            links.push(link);
+        }
+
+        Ok(links)
+    }
+
+    /**
+     * Returns the positions in [`SubnetCalculator::subnets`] of the subnets in the given order <br>
+     * The subnets are stored in the allocation order, which is [`SortOrder::Size`] with [`PackingStrategy::LargestFirst`]
+     */
+    pub fn sorted_positions(&self, order: SortOrder) -> Vec<usize> {
+        let mut positions: Vec<usize> = (0..self.subnets.len()).collect();
+        match order {
+            SortOrder::Input => positions.sort_by_key(|&i| self.input_indices[i]),
+            SortOrder::Network => positions.sort_by_key(|&i| self.subnets[i].network),
+            SortOrder::Size => positions.sort_by_key(|&i| Reverse(self.subnets[i].total_addresses)),
+        }
+        positions
+    }
+
+    /**
+     * Returns the number of subnets with the `child_cidr` that fit in a network with the `parent_cidr`,
+     * i.e. `2^(child_cidr - parent_cidr)` <br>
+     * Returns [`SubnetError::InvalidCidr`] if the child CIDR is shorter than the parent one or longer than /32,
+     * and [`SubnetError::TooManySubnets`] if the count doesn't fit in a `u32` (a /0 split into /32s)
+     */
+    pub fn count_subnets(parent_cidr: u32, child_cidr: u32) -> Result<u32, SubnetError> {
+        if parent_cidr > IPV4_BITS {
+            return Err(SubnetError::InvalidCidr(parent_cidr));
+        }
+        if child_cidr < parent_cidr || child_cidr > IPV4_BITS {
+            return Err(SubnetError::InvalidCidr(child_cidr));
+        }
+
+        let borrowed_bits = child_cidr - parent_cidr;
+        1u32.checked_shl(borrowed_bits)
+            .ok_or(SubnetError::TooManySubnets {
+                requested: 1 << borrowed_bits,
+                available: u32::MAX.into(),
+            })
+    }
+
+    /// Returns the index pairs of the subnets whose address ranges (network to broadcast) intersect
+    pub fn find_overlaps(&self) -> Vec<(usize, usize)> {
+        overlapping_pairs(&self.subnets)
+    }
+
+    /**
+     * Returns the index and the wasted hosts of the subnets wasting more than `threshold_percent` of their addresses
+     * see [`Subnet::wasted_percent`]
+     */
+    pub fn find_wasteful(&self, threshold_percent: f64) -> Vec<(usize, u32)> {
+        self.subnets
+            .iter()
+            .enumerate()
+            .filter(|(_, subnet)| subnet.wasted_percent() > threshold_percent)
+            .map(|(i, subnet)| (i, subnet.wasted_hosts()))
+            .collect()
+    }
+
+    /**
+     * Returns the smallest block (network and CIDR) covering all the subnets, used for route aggregation <br>
+     * It's the common prefix of the lowest network and the highest broadcast, or `None` if there are no subnets
+     */
+    pub fn summarize(&self) -> Option<(Ipv4Addr, u32)> {
+        let lowest = u32::from(self.subnets.iter().map(|s| s.network).min()?);
+        let highest = u32::from(self.subnets.iter().map(|s| s.broadcast).max()?);
+
+        let cidr = (lowest ^ highest).leading_zeros();
+        let mask = u32::MAX.checked_shl(IPV4_BITS - cidr).unwrap_or(0);
+        Some((Ipv4Addr::from(lowest & mask), cidr))
+    }
+
+    /**
+     * Returns the utilization of the parent network passed to [`SubnetCalculator::calculate`] <br>
+     * The total is 0 if no network has been calculated yet
+     */
+    pub fn utilization(&self) -> Utilization {
+        let total = self.parent_cidr.map_or(0, SubnetCalculator::block_size);
+        let allocated: u64 = self
+            .subnets
+            .iter()
+            .map(|s| SubnetCalculator::block_size(s.next_cidr))
+            .sum();
+        let usable = self.subnets.iter().map(|s| u64::from(s.real_hosts)).sum();
+        let hosts: u64 = self.subnets.iter().map(|s| u64::from(s.hosts)).sum();
+
+        let efficiency_percent = if allocated == 0 {
+            0.0
+        } else {
+            hosts as f64 / allocated as f64 * 100.0
+        };
+
+        Utilization {
+            total,
+            allocated,
+            usable,
+            efficiency_percent,
+        }
+    }
+
+    /// Helper function to get the number of addresses in a block with the given CIDR
+    fn block_size(cidr: u32) -> u64 {
+        math::addresses(cidr)
+    }
+}
+
+/// Implements the Display trait for the PrefixPreview struct to print a one-line summary (e.g. `50 hosts → /26 (62 usable, 12 wasted)`)
+impl Display for PrefixPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hosts → /{} ({} usable, {} wasted)",
+            self.hosts, self.prefix, self.usable, self.wasted
+        )
+    }
+}
+
+/// Implements the Display trait for the PackingWaste struct to print a one-line summary
+impl Display for PackingWaste {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} wasted addresses ({} in the subnets, {} in alignment gaps)",
+            self.total(),
+            self.wasted_hosts,
+            self.alignment_gaps
+        )
+    }
+}
+
+/// Implements the Display trait for the Utilization struct to print a one-line summary
+impl Display for Utilization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} addresses allocated, {} usable hosts, {:.2}% efficiency",
+            self.allocated, self.total, self.usable, self.efficiency_percent
+        )
+    }
+}
+
+/// Implements the Display trait for the PlanTotals struct to print a one-line footer (e.g. `Created 2 subnets; 92 usable hosts across 96 allocated addresses.`)
+impl Display for PlanTotals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Created {} subnet{}; {} usable hosts across {} allocated addresses.",
+            self.subnets,
+            if self.subnets == 1 { "" } else { "s" },
+            self.usable_hosts,
+            self.addresses
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subnets_exceeding_parent_are_rejected() {
+        let mut calculator = SubnetCalculator::new(vec![254, 254, 254]);
+        let result = calculator.calculate("192.168.1.0", 24);
+        assert!(matches!(
+            result,
+            Err(SubnetError::InsufficientSpace {
+                requested: 768,
+                available: 256,
+                first_overflow: Some(1)
+            })
+        ));
+        assert!(calculator.subnets.is_empty());
+    }
+
+    #[test]
+    fn streaming_yields_each_subnet_in_order() {
+        let mut calculator = SubnetCalculator::new(vec![10, 100, 50]);
+        let mut streamed = Vec::new();
+        calculator
+            .calculate_streaming("192.168.1.0", 24, |index, subnet| {
+                streamed.push((index, subnet.network, subnet.next_cidr))
+            })
+            .unwrap();
+        assert!(calculator.subnets.is_empty());
+
+        let mut buffered = SubnetCalculator::new(vec![10, 100, 50]);
+        buffered.calculate("192.168.1.0", 24).unwrap();
+        let expected: Vec<_> = buffered
+            .input_indices
+            .iter()
+            .zip(&buffered.subnets)
+            .map(|(&index, subnet)| (index, subnet.network, subnet.next_cidr))
+            .collect();
+        assert_eq!(streamed, expected);
+        assert_eq!(streamed.iter().map(|s| s.0).collect::<Vec<_>>(), [1, 2, 0]);
+    }
+
+    #[test]
+    fn streaming_yields_nothing_on_error() {
+        let mut calculator = SubnetCalculator::new(vec![254, 254, 254]);
+        let mut calls = 0;
+        let result = calculator.calculate_streaming("192.168.1.0", 24, |_, _| calls += 1);
+        assert!(matches!(
+            result,
+            Err(SubnetError::InsufficientSpace {
+                requested: 768,
+                available: 256,
+                first_overflow: Some(1)
+            })
+        ));
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn streaming_rejects_other_placements() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator.packing = PackingStrategy::BestFit;
+        assert!(matches!(
+            calculator.calculate_streaming("192.168.1.0", 24, |_, _| {}),
+            Err(SubnetError::UnsupportedStreaming(packing)) if packing == "best-fit"
+        ));
+
+        calculator.packing = PackingStrategy::LargestFirst;
+        calculator.fill_direction = FillDirection::TopDown;
+        assert!(matches!(
+            calculator.calculate_streaming("192.168.1.0", 24, |_, _| {}),
+            Err(SubnetError::UnsupportedStreaming(_))
+        ));
+    }
+
+    #[test]
+    fn pool_request_lands_in_the_gap() {
+        // 10.0.0.64/26 is the only free block of the /24
+        let allocated = [
+            SubnetBuilder::new("10.0.0.0", 26, 50).build().unwrap(),
+            SubnetBuilder::new("10.0.0.128", 25, 100).build().unwrap(),
+        ];
+        let mut calculator = SubnetCalculator::new(vec![20, 30]);
+        calculator
+            .calculate_in_pool("10.0.0.0", 24, &allocated)
+            .unwrap();
+
+        let networks: Vec<String> = calculator
+            .subnets
+            .iter()
+            .map(|s| format!("{}/{}", s.network, s.next_cidr))
+            .collect();
+        assert_eq!(networks, ["10.0.0.64/27", "10.0.0.96/27"]);
+        assert_eq!(calculator.input_indices, vec![1, 0]);
+    }
+
+    #[test]
+    fn merged_plan_keeps_existing_subnets() {
+        let mut plan = calculate_vlsm("10.0.0.0", 24, &[100, 20]).unwrap();
+        plan[1].name = Some("Lab".to_string());
+        let saved = format!("{:?}", plan);
+
+        // The plan uses 10.0.0.0/25 and 10.0.0.128/27, the new department fills the gap after them
+        let mut calculator = SubnetCalculator::new(vec![50]);
+        calculator.names = vec!["Sales".to_string()];
+        let merged = calculator.merge_plan("10.0.0.0", 24, &plan).unwrap();
+
+        assert_eq!(format!("{:?}", &merged[..2]), saved);
+        assert_eq!(merged[2].network, Ipv4Addr::new(10, 0, 0, 192));
+        assert_eq!(merged[2].next_cidr, 26);
+        assert_eq!(merged[2].name.as_deref(), Some("Sales"));
+        assert_eq!(calculator.subnets.len(), 1);
+    }
+
+    #[test]
+    fn pool_skips_gaps_too_small_or_misaligned() {
+        // Free: 10.0.0.16/28 and 10.0.0.96-10.0.0.255
+        let allocated = [
+            SubnetBuilder::new("10.0.0.0", 28, 10).build().unwrap(),
+            SubnetBuilder::new("10.0.0.32", 27, 20).build().unwrap(),
+            SubnetBuilder::new("10.0.0.64", 27, 20).build().unwrap(),
+        ];
+        let mut calculator = SubnetCalculator::new(vec![10, 100]);
+        calculator
+            .calculate_in_pool("10.0.0.0", 24, &allocated)
+            .unwrap();
+        assert_eq!(calculator.subnets[0].network, Ipv4Addr::new(10, 0, 0, 128));
+        assert_eq!(calculator.subnets[1].network, Ipv4Addr::new(10, 0, 0, 16));
+
+        let mut calculator = SubnetCalculator::new(vec![100, 100]);
+        assert!(matches!(
+            calculator.calculate_in_pool("10.0.0.0", 24, &allocated),
+            Err(SubnetError::InsufficientSpace {
+                requested: 128,
+                available: 32,
+                first_overflow: Some(1)
+            })
+        ));
+        assert!(calculator.subnets.is_empty());
+    }
+
+    #[test]
+    fn empty_pool_allocates_from_the_start() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator
+            .calculate_in_pool("192.168.1.0", 24, &[])
+            .unwrap();
+
+        let mut sequential = SubnetCalculator::new(vec![50, 20]);
+        sequential.calculate("192.168.1.0", 24).unwrap();
+        assert_eq!(calculator.subnets, sequential.subnets);
+    }
+
+    #[test]
+    fn find_subnet_of_address() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20, 10]);
+        calculator.names = vec![
+            "Office".to_string(),
+            "Lab".to_string(),
+            "Guests".to_string(),
+        ];
+        calculator.calculate("10.0.0.0", 24).unwrap();
+
+        // 10.0.0.45 is a host of the /26 of the office, and of no other subnet
+        let (index, subnet) =
+            find_subnet(&calculator.subnets, Ipv4Addr::new(10, 0, 0, 45)).unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(subnet.name.as_deref(), Some("Office"));
+        assert_eq!(
+            calculator
+                .subnets
+                .iter()
+                .filter(|s| s.contains(Ipv4Addr::new(10, 0, 0, 45)))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn find_subnet_of_unallocated_address() {
+        let subnets = calculate_vlsm("10.0.0.0", 24, &[50, 20, 10]).unwrap();
+
+        // The subnets end at 10.0.0.111, the rest of the /24 is free
+        assert!(find_subnet(&subnets, Ipv4Addr::new(10, 0, 0, 200)).is_none());
+        assert!(find_subnet(&subnets, Ipv4Addr::new(192, 168, 1, 1)).is_none());
+    }
+
+    #[test]
+    fn calculate_vlsm_keeps_the_hosts_order() {
+        let hosts = [10, 100, 50];
+        let subnets = calculate_vlsm("192.168.1.0", 24, &hosts).unwrap();
+
+        assert_eq!(hosts, [10, 100, 50]);
+        assert_eq!(
+            subnets.iter().map(|s| s.hosts).collect::<Vec<_>>(),
+            [100, 50, 10]
+        );
+
+        let mut calculator = SubnetCalculator::new(hosts.to_vec());
+        calculator.calculate("192.168.1.0", 24).unwrap();
+        assert_eq!(subnets, calculator.subnets);
+    }
+
+    #[test]
+    fn parent_network_is_retained() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        assert_eq!(calculator.parent_network(), None);
+        assert_eq!(calculator.parent_cidr(), None);
+
+        calculator.calculate("192.168.1.0", 24).unwrap();
+        assert_eq!(
+            calculator.parent_network(),
+            Some(Ipv4Addr::new(192, 168, 1, 0))
+        );
+        assert_eq!(calculator.parent_cidr(), Some(24));
+    }
+
+    #[test]
+    fn subnets_reaching_top_of_address_space_terminate() {
+        let mut calculator = SubnetCalculator::new(vec![126, 126]);
+        calculator.calculate("255.255.255.0", 24).unwrap();
+        assert_eq!(calculator.subnets.len(), 2);
+        assert_eq!(calculator.subnets[1].broadcast, Ipv4Addr::BROADCAST);
+        assert_eq!(calculator.subnets[1].next_subnet, None);
+
+        let mut calculator = SubnetCalculator::new(vec![126, 126, 10]);
+        assert!(matches!(
+            calculator.calculate("255.255.255.0", 24),
+            Err(SubnetError::AddressSpaceExhausted)
+        ));
+        assert!(calculator.subnets.is_empty());
+    }
+
+    #[test]
+    fn prefixes_mixed_with_hosts() {
+        let requirements: Vec<HostRequirement> = ["/28", "50", "/30", "/30"]
+            .iter()
+            .map(|r| r.parse().unwrap())
+            .collect();
+        let mut calculator = SubnetCalculator::from_requirements(&requirements);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let prefixes: Vec<u32> = calculator.subnets.iter().map(|s| s.next_cidr).collect();
+        assert_eq!(prefixes, [26, 28, 30, 30]);
+        let requested: Vec<Option<u32>> = calculator
+            .subnets
+            .iter()
+            .map(|s| s.requested_prefix)
+            .collect();
+        assert_eq!(requested, [None, Some(28), Some(30), Some(30)]);
+        assert_eq!(
+            calculator.subnets[1].network,
+            Ipv4Addr::new(192, 168, 1, 64)
+        );
+    }
+
+    #[test]
+    fn preview_maps_hosts_to_prefixes() {
+        let calculator = SubnetCalculator::new(vec![10, 50]);
+        let preview = calculator.preview().unwrap();
+        assert_eq!(
+            preview.iter().map(|p| p.prefix).collect::<Vec<_>>(),
+            [28, 26]
+        );
+        assert_eq!(
+            preview[1].to_string(),
+            "50 hosts → /26 (62 usable, 12 wasted)"
+        );
+        assert!(calculator.subnets.is_empty());
+    }
+
+    #[test]
+    fn sizing_output_is_pinned() {
+        // Integer math and Rust's formatting, without thousands separators, so the output is the same on every platform
+        let calculator = SubnetCalculator::new(vec![1000, 1_000_000]);
+        let preview = calculator.preview().unwrap();
+        assert_eq!(
+            preview[0].to_string(),
+            "1000 hosts → /22 (1022 usable, 22 wasted)"
+        );
+        assert_eq!(
+            preview[1].to_string(),
+            "1000000 hosts → /12 (1048574 usable, 48574 wasted)"
+        );
+
+        let subnets = calculate_vlsm("10.0.0.0", 16, &[1000]).unwrap();
+        assert_eq!(
+            subnets[0].summary(),
+            "10.0.0.0/22  hosts 1022  bcast 10.0.3.255  gw 10.0.3.254"
+        );
+        assert_eq!(
+            plan_totals(&subnets).to_string(),
+            "Created 1 subnet; 1022 usable hosts across 1024 allocated addresses."
+        );
+    }
+
+    #[test]
+    fn packing_strategies_place_mixed_hosts() {
+        let networks = |packing| {
+            let mut calculator = SubnetCalculator::new(vec![10, 50, 2, 20]);
+            calculator.packing = packing;
+            calculator.calculate("192.168.1.0", 24).unwrap();
+            calculator
+                .sorted_positions(SortOrder::Input)
+                .into_iter()
+                .map(|i| {
+                    let subnet = &calculator.subnets[i];
+                    format!("{}/{}", subnet.network, subnet.next_cidr)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            networks(PackingStrategy::LargestFirst),
+            [
+                "192.168.1.96/28",
+                "192.168.1.0/26",
+                "192.168.1.112/30",
+                "192.168.1.64/27"
+            ]
+        );
+        assert_eq!(
+            networks(PackingStrategy::SmallestFirst),
+            [
+                "192.168.1.16/28",
+                "192.168.1.64/26",
+                "192.168.1.0/30",
+                "192.168.1.32/27"
+            ]
+        );
+        // The /30 goes in the smallest gap left by the /28 and the /26, the 48 addresses before the /26
+        assert_eq!(
+            networks(PackingStrategy::BestFit),
+            [
+                "192.168.1.0/28",
+                "192.168.1.64/26",
+                "192.168.1.16/30",
+                "192.168.1.32/27"
+            ]
+        );
+    }
+
+    #[test]
+    fn compare_packing_reports_waste_per_strategy() {
+        let calculator = SubnetCalculator::new(vec![10, 50, 2, 20]);
+        let waste: Vec<_> = calculator
+            .compare_packing("192.168.1.0", 24)
+            .into_iter()
+            .map(|(packing, waste)| (packing, waste.unwrap()))
+            .collect();
+
+        assert_eq!(
+            waste,
+            [
+                (
+                    PackingStrategy::LargestFirst,
+                    PackingWaste {
+                        wasted_hosts: 34,
+                        alignment_gaps: 0
+                    }
+                ),
+                (
+                    PackingStrategy::SmallestFirst,
+                    PackingWaste {
+                        wasted_hosts: 34,
+                        alignment_gaps: 12
+                    }
+                ),
+                (
+                    PackingStrategy::BestFit,
+                    PackingWaste {
+                        wasted_hosts: 34,
+                        alignment_gaps: 12
+                    }
+                ),
+            ]
+        );
+        assert_eq!(waste[1].1.total(), 46);
+        assert!(calculator.subnets.is_empty());
+    }
+
+    #[test]
+    fn top_down_fills_from_the_end_of_the_network() {
+        let mut calculator = SubnetCalculator::new(vec![20, 50]);
+        calculator.fill_direction = FillDirection::TopDown;
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        // The /26 ends at the broadcast address of the /24 and the /27 is its predecessor
+        let networks: Vec<String> = calculator
+            .subnets
+            .iter()
+            .map(|s| format!("{}/{}", s.network, s.next_cidr))
+            .collect();
+        assert_eq!(networks, ["192.168.1.192/26", "192.168.1.160/27"]);
+        assert_eq!(
+            calculator.subnets[0].broadcast,
+            Ipv4Addr::new(192, 168, 1, 255)
+        );
+        assert_eq!(calculator.input_indices, [1, 0]);
+        assert_eq!(calculator.packing_waste().alignment_gaps, 0);
+    }
+
+    #[test]
+    fn top_down_with_smallest_first_and_overflow() {
+        let mut calculator = SubnetCalculator::new(vec![20, 50]);
+        calculator.fill_direction = FillDirection::TopDown;
+        calculator.packing = PackingStrategy::SmallestFirst;
+        calculator.calculate("192.168.1.0", 24).unwrap();
+        assert_eq!(
+            calculator.subnets[0].network,
+            Ipv4Addr::new(192, 168, 1, 224)
+        );
+        assert_eq!(
+            calculator.subnets[1].network,
+            Ipv4Addr::new(192, 168, 1, 128)
+        );
+
+        let mut calculator = SubnetCalculator::new(vec![100, 100]);
+        calculator.fill_direction = FillDirection::TopDown;
+        assert!(calculator.calculate("192.168.1.0", 25).is_err());
+    }
+
+    #[test]
+    fn fill_direction_from_str() {
+        assert_eq!(
+            "Top-Down".parse::<FillDirection>().unwrap(),
+            FillDirection::TopDown
+        );
+        assert!(matches!(
+            "sideways".parse::<FillDirection>(),
+            Err(SubnetError::InvalidFillDirection(_))
+        ));
+    }
+
+    #[test]
+    fn alignment_leaves_gaps_between_subnets() {
+        let mut calculator = SubnetCalculator::new(vec![10, 2, 2]);
+        calculator.align = Some(28);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+
+        // Packed, the /30s would be at .16 and .20, aligned each of them starts on a /28 boundary
+        let networks: Vec<String> = calculator
+            .subnets
+            .iter()
+            .map(|s| format!("{}/{}", s.network, s.next_cidr))
+            .collect();
+        assert_eq!(networks, ["10.0.0.0/28", "10.0.0.16/30", "10.0.0.32/30"]);
+        assert_eq!(calculator.packing_waste().alignment_gaps, 12);
+
+        calculator.packing = PackingStrategy::SmallestFirst;
+        calculator.recalculate(&calculator.requirements()).unwrap();
+        let networks: Vec<String> = calculator
+            .subnets
+            .iter()
+            .map(|s| format!("{}/{}", s.network, s.next_cidr))
+            .collect();
+        assert_eq!(networks, ["10.0.0.0/30", "10.0.0.16/30", "10.0.0.32/28"]);
+    }
+
+    #[test]
+    fn alignment_gaps_count_towards_space() {
+        let mut calculator = SubnetCalculator::new(vec![2, 2, 2]);
+        calculator.align = Some(28);
+
+        // Three /30s fit in a /28 when packed, but each of them takes a /28 when aligned
+        let error = calculator.calculate("10.0.0.0", 28).unwrap_err();
+        assert!(matches!(
+            error,
+            SubnetError::InsufficientSpace {
+                first_overflow: Some(1),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn packing_strategy_from_str() {
+        assert_eq!(
+            "Best-Fit".parse::<PackingStrategy>().unwrap(),
+            PackingStrategy::BestFit
+        );
+        assert!(matches!(
+            "tightest".parse::<PackingStrategy>(),
+            Err(SubnetError::InvalidPackingStrategy(_))
+        ));
+    }
+
+    #[test]
+    fn recalculate_after_editing_hosts() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20, 10]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let mut requirements = calculator.requirements();
+        requirements[1] = HostRequirement::Hosts(100);
+        requirements.remove(2);
+        requirements.push(HostRequirement::Prefix(30));
+        calculator.recalculate(&requirements).unwrap();
+
+        let hosts: Vec<u32> = calculator.subnets.iter().map(|s| s.hosts).collect();
+        assert_eq!(hosts, [100, 50, 2]);
+        assert_eq!(calculator.requirements(), requirements);
+        assert_eq!(calculator.parent_cidr(), Some(24));
+    }
+
+    #[test]
+    fn growth_keeps_the_requested_hosts() {
+        let requirements = [
+            HostRequirement::Growth {
+                hosts: 50,
+                grown: 65,
+            },
+            HostRequirement::Hosts(20),
+        ];
+        let mut calculator = SubnetCalculator::from_requirements(&requirements);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let subnet = &calculator.subnets[0];
+        assert_eq!(subnet.next_cidr, 25);
+        assert_eq!(subnet.hosts, 65);
+        assert_eq!(subnet.requested_hosts, Some(50));
+        assert!(subnet
+            .to_string()
+            .contains("Hosts: 65 (requested 50 plus growth)"));
+        assert_eq!(calculator.subnets[1].requested_hosts, None);
+        assert_eq!(calculator.requirements(), requirements);
+    }
+
+    #[test]
+    fn names_follow_the_input_order() {
+        let mut calculator = SubnetCalculator::new(vec![10, 100, 50]);
+        calculator.names = vec!["Guests".to_string(), String::new(), "Office".to_string()];
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let names: Vec<Option<&str>> = calculator
+            .subnets
+            .iter()
+            .map(|s| s.name.as_deref())
+            .collect();
+        assert_eq!(names, [None, Some("Office"), Some("Guests")]);
+
+        calculator.recalculate(&calculator.requirements()).unwrap();
+        assert_eq!(calculator.subnets[2].name.as_deref(), Some("Guests"));
+    }
+
+    #[test]
+    fn failed_recalculation_keeps_previous_subnets() {
+        let mut calculator = SubnetCalculator::new(vec![50]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let result =
+            calculator.recalculate(&[HostRequirement::Hosts(200), HostRequirement::Hosts(100)]);
+        assert!(matches!(result, Err(SubnetError::InsufficientSpace { .. })));
+        assert_eq!(calculator.subnets.len(), 1);
+        assert_eq!(calculator.requirements(), [HostRequirement::Hosts(50)]);
+    }
+
+    #[test]
+    fn subnets_filling_parent_are_accepted() {
+        let mut calculator = SubnetCalculator::new(vec![126, 62, 62]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+        assert_eq!(calculator.subnets.len(), 3);
+    }
+
+    #[test]
+    fn split_equal_into_four() {
+        let subnets = SubnetCalculator::split_equal("10.0.0.0", 24, 4).unwrap();
+        let networks: Vec<String> = subnets.iter().map(|s| s.network.to_string()).collect();
+        assert_eq!(
+            networks,
+            ["10.0.0.0", "10.0.0.64", "10.0.0.128", "10.0.0.192"]
+        );
+        assert!(subnets.iter().all(|s| s.next_cidr == 26));
+    }
+
+    #[test]
+    fn split_equal_rounds_up_to_power_of_two() {
+        let subnets = SubnetCalculator::split_equal("10.0.0.0", 24, 3).unwrap();
+        assert_eq!(subnets.len(), 3);
+        assert!(subnets.iter().all(|s| s.next_cidr == 26));
+    }
+
+    #[test]
+    fn split_equal_rejects_too_many_subnets() {
+        assert!(matches!(
+            SubnetCalculator::split_equal("10.0.0.0", 28, 5),
+            Err(SubnetError::TooManySubnets {
+                requested: 5,
+                available: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn split_equal_of_whole_address_space() {
+        let subnets = SubnetCalculator::split_equal("0.0.0.0", 0, 1).unwrap();
+        assert_eq!(subnets[0].next_cidr, 0);
+        assert_eq!(subnets[0].real_hosts, u32::MAX - 1);
+
+        assert!(matches!(
+            SubnetCalculator::split_equal("10.0.0.0", 24, 0),
+            Err(SubnetError::ZeroSubnets)
+        ));
+    }
+
+    #[test]
+    fn split_ptp_into_slash_30s() {
+        let links = SubnetCalculator::split_ptp("10.0.0.0", 28, 4, false).unwrap();
+        let networks: Vec<String> = links
+            .iter()
+            .map(|l| format!("{}/{}", l.network, l.next_cidr))
+            .collect();
+        assert_eq!(
+            networks,
+            ["10.0.0.0/30", "10.0.0.4/30", "10.0.0.8/30", "10.0.0.12/30"]
+        );
+        assert!(links.iter().all(|l| l.real_hosts == 2));
+
+        assert!(matches!(
+            SubnetCalculator::split_ptp("10.0.0.0", 28, 5, false),
+            Err(SubnetError::TooManySubnets {
+                requested: 5,
+                available: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn split_ptp_into_slash_31s() {
+        let links = SubnetCalculator::split_ptp("10.0.0.0", 28, 8, true).unwrap();
+        assert_eq!(links.len(), 8);
+        assert!(links.iter().all(|l| l.next_cidr == 31 && l.real_hosts == 2));
+        assert_eq!(links[7].network, Ipv4Addr::new(10, 0, 0, 14));
+        assert_eq!(links[7].broadcast, Ipv4Addr::new(10, 0, 0, 15));
+
+        assert!(matches!(
+            SubnetCalculator::split_ptp("10.0.0.0", 28, 9, true),
+            Err(SubnetError::TooManySubnets { .. })
+        ));
+    }
+
+    #[test]
+    fn input_order_differs_from_packed_order() {
+        let mut calculator = SubnetCalculator::new(vec![10, 100, 50]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let packed: Vec<u32> = calculator
+            .sorted_positions(SortOrder::Size)
+            .iter()
+            .map(|&i| calculator.subnets[i].hosts)
+            .collect();
+        assert_eq!(packed, [100, 50, 10]);
+
+        let input: Vec<u32> = calculator
+            .sorted_positions(SortOrder::Input)
+            .iter()
+            .map(|&i| calculator.subnets[i].hosts)
+            .collect();
+        assert_eq!(input, [10, 100, 50]);
+        assert_ne!(input, packed);
+    }
+
+    #[test]
+    fn sort_order_from_str() {
+        assert_eq!("Network".parse::<SortOrder>().unwrap(), SortOrder::Network);
+        assert!(matches!(
+            "random".parse::<SortOrder>(),
+            Err(SubnetError::InvalidSortOrder(_))
+        ));
+    }
+
+    #[test]
+    fn count_slash_24s_in_slash_16() {
+        assert_eq!(SubnetCalculator::count_subnets(16, 24).unwrap(), 256);
+        assert_eq!(SubnetCalculator::count_subnets(24, 24).unwrap(), 1);
+    }
+
+    #[test]
+    fn count_subnets_rejects_invalid_cidrs() {
+        assert!(matches!(
+            SubnetCalculator::count_subnets(24, 16),
+            Err(SubnetError::InvalidCidr(16))
+        ));
+        assert!(matches!(
+            SubnetCalculator::count_subnets(24, 33),
+            Err(SubnetError::InvalidCidr(33))
+        ));
+        assert!(matches!(
+            SubnetCalculator::count_subnets(0, 32),
+            Err(SubnetError::TooManySubnets { .. })
+        ));
+    }
+
+    #[test]
+    fn overlapping_subnets_are_reported() {
+        let mut a = Subnet::new("192.168.1.0", 24, 100).unwrap();
+        a.calculate().unwrap();
+        let mut b = Subnet::new("192.168.1.64", 26, 20).unwrap();
+        b.calculate().unwrap();
+        let mut c = Subnet::new("192.168.1.128", 25, 20).unwrap();
+        c.calculate().unwrap();
+
+        let mut calculator = SubnetCalculator::new(Vec::new());
+        calculator.subnets = vec![a, b, c];
+        assert_eq!(calculator.find_overlaps(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn calculated_subnets_dont_overlap() {
+        let mut calculator = SubnetCalculator::new(vec![100, 50, 20, 10]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+        assert!(calculator.find_overlaps().is_empty());
+    }
+
+    #[test]
+    fn three_hosts_waste_more_than_half() {
+        let mut calculator = SubnetCalculator::new(vec![50, 3]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        // 3 hosts in a /29 waste 5 of the 8 addresses, 50 hosts in a /26 only 14 of 64
+        assert_eq!(calculator.subnets[1].next_cidr, 29);
+        assert_eq!(calculator.find_wasteful(50.0), vec![(1, 5)]);
+        assert_eq!(calculator.find_wasteful(70.0), vec![]);
+        assert_eq!(calculator.find_wasteful(25.0), vec![(1, 5)]);
+        assert_eq!(calculator.find_wasteful(10.0).len(), 2);
+    }
+
+    #[test]
+    fn summarize_covers_all_subnets() {
+        let mut calculator = SubnetCalculator::new(vec![62, 62, 62]);
+        calculator.calculate("10.0.0.0", 24).unwrap();
+        assert_eq!(
+            calculator.summarize(),
+            Some((Ipv4Addr::new(10, 0, 0, 0), 24))
+        );
+    }
+
+    #[test]
+    fn summarize_single_subnet() {
+        let mut calculator = SubnetCalculator::new(vec![62]);
+        calculator.calculate("10.0.0.64", 26).unwrap();
+        assert_eq!(
+            calculator.summarize(),
+            Some((Ipv4Addr::new(10, 0, 0, 64), 26))
+        );
+    }
+
+    #[test]
+    fn aggregate_merges_sibling_prefixes() {
+        let prefixes = [
+            (Ipv4Addr::new(10, 0, 0, 128), 25),
+            (Ipv4Addr::new(10, 0, 0, 0), 25),
+        ];
+        assert_eq!(aggregate(&prefixes), vec![(Ipv4Addr::new(10, 0, 0, 0), 24)]);
+
+        // The merged /25 is merged again with the other /25, and the covered /26 is dropped
+        let prefixes = [
+            (Ipv4Addr::new(192, 168, 0, 0), 25),
+            (Ipv4Addr::new(192, 168, 0, 128), 26),
+            (Ipv4Addr::new(192, 168, 0, 192), 26),
+            (Ipv4Addr::new(192, 168, 0, 64), 26),
+        ];
+        assert_eq!(
+            aggregate(&prefixes),
+            vec![(Ipv4Addr::new(192, 168, 0, 0), 24)]
+        );
+    }
+
+    #[test]
+    fn aggregate_keeps_non_sibling_prefixes() {
+        // Adjacent but not siblings: 10.0.0.128/25 and 10.0.1.0/25 aren't the halves of a /24
+        let prefixes = [
+            (Ipv4Addr::new(10, 0, 1, 0), 25),
+            (Ipv4Addr::new(10, 0, 0, 128), 25),
+        ];
+        assert_eq!(
+            aggregate(&prefixes),
+            vec![
+                (Ipv4Addr::new(10, 0, 0, 128), 25),
+                (Ipv4Addr::new(10, 0, 1, 0), 25)
+            ]
+        );
+        assert!(aggregate(&[]).is_empty());
+    }
+
+    #[test]
+    fn summarize_empty() {
+        let calculator = SubnetCalculator::new(Vec::new());
+        assert_eq!(calculator.summarize(), None);
+    }
+
+    #[test]
+    fn input_indices_follow_allocation_order() {
+        let mut calculator = SubnetCalculator::new(vec![10, 50, 20]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+        assert_eq!(calculator.input_indices, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn utilization_of_two_subnets() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator.calculate("192.168.1.0", 24).unwrap();
+
+        let utilization = calculator.utilization();
+        assert_eq!(utilization.total, 256);
+        assert_eq!(utilization.allocated, 96);
+        assert_eq!(utilization.usable, 92);
+        assert!((utilization.efficiency_percent - 70.0 / 96.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn totals_of_two_subnets() {
+        let subnets = calculate_vlsm("192.168.1.0", 24, &[50, 20]).unwrap();
+        let totals = plan_totals(&subnets);
+        assert_eq!(
+            totals,
+            PlanTotals {
+                subnets: 2,
+                usable_hosts: 92,
+                addresses: 96
+            }
+        );
+        assert_eq!(
+            totals.to_string(),
+            "Created 2 subnets; 92 usable hosts across 96 allocated addresses."
+        );
+        assert_eq!(
+            plan_totals(&subnets[..1]).to_string(),
+            "Created 1 subnet; 62 usable hosts across 64 allocated addresses."
+        );
+    }
+
+    #[test]
+    fn allocation_starts_at_offset() {
+        let mut calculator = SubnetCalculator::new(vec![50, 20]);
+        calculator.start_at = Some(Ipv4Addr::new(10, 0, 0, 64));
+        calculator.calculate("10.0.0.0", 24).unwrap();
+        assert_eq!(calculator.subnets[0].network, Ipv4Addr::new(10, 0, 0, 64));
+        assert_eq!(calculator.subnets[1].network, Ipv4Addr::new(10, 0, 0, 128));
+
+        let mut calculator = SubnetCalculator::new(vec![20, 50]);
+        calculator.packing = PackingStrategy::BestFit;
+        calculator.start_at = Some(Ipv4Addr::new(10, 0, 0, 64));
+        calculator.calculate("10.0.0.0", 24).unwrap();
+        assert_eq!(calculator.subnets[0].network, Ipv4Addr::new(10, 0, 0, 64));
+        assert_eq!(calculator.subnets[1].network, Ipv4Addr::new(10, 0, 0, 128));
+    }
+
+    #[test]
+    fn skipped_addresses_count_towards_space() {
+        // The /26 before the start and the three /26 and the /27 need 288 addresses
+        let mut calculator = SubnetCalculator::new(vec![50, 50, 50, 20]);
+        calculator.start_at = Some(Ipv4Addr::new(10, 0, 0, 64));
+        assert!(matches!(
+            calculator.calculate("10.0.0.0", 24),
+            Err(SubnetError::InsufficientSpace {
+                requested: 288,
+                available: 256,
+                first_overflow: Some(3)
+            })
+        ));
+    }
+
+    #[test]
+    fn start_outside_parent_is_an_error() {
+        let mut calculator = SubnetCalculator::new(vec![50]);
+        calculator.start_at = Some(Ipv4Addr::new(10, 0, 1, 0));
+        assert!(matches!(
+            calculator.calculate("10.0.0.0", 24),
+            Err(SubnetError::HostOutsideSubnet {
+                role: "start address",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unaligned_start_is_an_error() {
+        let mut calculator = SubnetCalculator::new(vec![20]);
+        calculator.start_at = Some(Ipv4Addr::new(10, 0, 0, 65));
+        assert!(matches!(
+            calculator.calculate("10.0.0.0", 24),
+            Err(SubnetError::UnalignedStart { prefix: 27, .. })
+        ));
+
+        // The largest subnet isn't moved to the next free block of its size, even if there's one
+        let mut calculator = SubnetCalculator::new(vec![100]);
+        calculator.start_at = Some(Ipv4Addr::new(10, 0, 0, 64));
+        assert!(matches!(
+            calculator.calculate("10.0.0.0", 24),
+            Err(SubnetError::UnalignedStart { prefix: 25, .. })
+        ));
+        calculator.packing = PackingStrategy::BestFit;
+        assert!(matches!(
+            calculator.calculate("10.0.0.0", 24),
+            Err(SubnetError::UnalignedStart { prefix: 25, .. })
+        ));
+    }
+
+    #[test]
+    fn recalculate_keeps_start() {
+        let mut calculator = SubnetCalculator::new(vec![50]);
+        calculator.start_at = Some(Ipv4Addr::new(10, 0, 0, 64));
+        calculator.calculate("10.0.0.0", 24).unwrap();
+        calculator
+            .recalculate(&[HostRequirement::Hosts(50), HostRequirement::Hosts(20)])
+            .unwrap();
+        assert_eq!(calculator.start_at, Some(Ipv4Addr::new(10, 0, 0, 64)));
+        assert_eq!(calculator.subnets[0].network, Ipv4Addr::new(10, 0, 0, 64));
+        assert_eq!(calculator.subnets[1].network, Ipv4Addr::new(10, 0, 0, 128));
+
+        // A new largest subnet must still be aligned to the start, the calculator is left unchanged otherwise
+        assert!(matches!(
+            calculator.recalculate(&[HostRequirement::Hosts(100)]),
+            Err(SubnetError::UnalignedStart { .. })
+        ));
+        assert_eq!(calculator.subnets.len(), 2);
+    }
+
+    #[test]
+    fn calculated_plan_passes_check() {
+        let subnets = calculate_vlsm("10.0.0.0", 24, &[100, 50, 2]).unwrap();
+        let parent = "10.0.0.0/24".parse().ok();
+        assert!(check_plan(&subnets, parent).is_empty());
+    }
+
+    #[test]
+    fn corrupted_plan_fails_check() {
+        let mut subnets = calculate_vlsm("10.0.0.0", 24, &[100, 50, 20]).unwrap();
+        subnets[1].broadcast = Ipv4Addr::new(10, 0, 0, 255);
+        subnets[2].network = Ipv4Addr::new(10, 0, 0, 32);
+
+        let discrepancies = check_plan(&subnets, "10.0.0.0/25".parse().ok());
+        assert!(discrepancies.contains(&(
+            1,
+            "broadcast is 10.0.0.255, expected 10.0.0.191".to_string()
+        )));
+        assert!(discrepancies.contains(&(0, "overlaps subnet #3".to_string())));
+        assert!(
+            discrepancies.contains(&(1, "isn't inside the parent network 10.0.0.0/25".to_string()))
+        );
+        assert!(subnets[0].discrepancies().is_empty());
+    }
+
+    #[test]
+    fn utilization_before_calculation() {
+        let calculator = SubnetCalculator::new(vec![50]);
+        let utilization = calculator.utilization();
+        assert_eq!(utilization.total, 0);
+        assert_eq!(utilization.efficiency_percent, 0.0);
+    }
+
+    #[test]
+    fn point_to_point_links_are_packed() {
+        let mut calculator = SubnetCalculator::new(vec![2, 2]);
+        calculator.point_to_point = true;
+        calculator.calculate("10.0.0.0", 30).unwrap();
+        assert_eq!(calculator.subnets[1].network.to_string(), "10.0.0.2");
+        assert_eq!(calculator.subnets[1].next_cidr, 31);
+    }
 }
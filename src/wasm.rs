@@ -0,0 +1,51 @@
+//! Entry point for WebAssembly builds (e.g. a browser-based calculator), with JSON strings in and out <br>
+//! It doesn't depend on `wasm-bindgen`, the function can be exported with a one-line wrapper:
+//! ```text
+//! #[wasm_bindgen]
+//! pub fn calculate(network: &str, cidr: u32, hosts_json: &str) -> Result<String, String> {
+//!     subnetting::wasm::calculate_json(network, cidr, hosts_json)
+//! }
+//! ```
+
+use crate::save_file::write_json;
+use crate::subnets_calculator::calculate_vlsm;
+
+/**
+ * Calculates the subnets of the network for a JSON array of numbers of hosts (e.g. `[50, 20, 10]`) <br>
+ * It returns the subnets as a JSON array with the same fields as [`write_json`],
+ * every error (invalid JSON, invalid network, not enough space, ...) being returned as its message
+ */
+pub fn calculate_json(network: &str, cidr: u32, hosts_json: &str) -> Result<String, String> {
+    let hosts: Vec<u32> = serde_json::from_str(hosts_json).map_err(|e| e.to_string())?;
+    let subnets = calculate_vlsm(network, cidr, &hosts).map_err(|e| e.to_string())?;
+
+    let mut output = Vec::new();
+    write_json(&subnets, &mut output).map_err(|e| e.to_string())?;
+    String::from_utf8(output).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_subnets_of_json_hosts() {
+        let output = calculate_json("192.168.1.0", 24, "[50, 20]").unwrap();
+        let subnets: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(subnets.as_array().unwrap().len(), 2);
+        assert_eq!(subnets[0]["network"], "192.168.1.0");
+        assert_eq!(subnets[0]["prefix"], 26);
+        assert_eq!(subnets[1]["network"], "192.168.1.64");
+        assert_eq!(subnets[1]["prefix"], 27);
+    }
+
+    #[test]
+    fn errors_are_strings() {
+        assert!(calculate_json("192.168.1.0", 24, "[50,")
+            .unwrap_err()
+            .contains("EOF"));
+        assert!(calculate_json("192.168.1.0", 24, "[254, 254]").is_err());
+        assert!(calculate_json("192.168.1", 24, "[50]").is_err());
+    }
+}
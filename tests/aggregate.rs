@@ -0,0 +1,22 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::run;
+
+#[test]
+fn aggregated_prefixes_are_printed_one_per_line() {
+    let output = run(
+        &[
+            "--aggregate",
+            "10.0.1.0/25,10.0.0.128/25,10.0.0.0/25,10.0.0.64/26",
+        ],
+        "",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "10.0.0.0/24\n10.0.1.0/25\n"
+    );
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::run;
+
+#[test]
+fn benchmark_reports_elapsed_time() {
+    let output = run(
+        &[
+            "-n",
+            "10.0.0.0/16",
+            "--hosts",
+            "500,200,50,2",
+            "-f",
+            "prefixes",
+            "--benchmark",
+        ],
+        "",
+    );
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let line = stderr
+        .lines()
+        .find(|line| line.starts_with("Benchmark: 4 subnets calculated in "))
+        .unwrap();
+    let elapsed: f64 = line
+        .split_once(" in ")
+        .and_then(|(_, rest)| rest.split_once(" ms"))
+        .unwrap()
+        .0
+        .parse()
+        .unwrap();
+    assert!(elapsed >= 0.0);
+    assert!(line.contains("subnets/s"));
+
+    // The report doesn't change the results printed to the standard output
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "10.0.0.0/23\n10.0.2.0/24\n10.0.3.0/26\n10.0.3.64/30\n"
+    );
+}
+
+#[test]
+fn no_benchmark_without_the_flag() {
+    let output = run(
+        &["-n", "10.0.0.0/16", "--hosts", "500", "-f", "prefixes"],
+        "",
+    );
+    assert!(!String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Benchmark"));
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::run;
+
+#[test]
+fn saved_plan_passes_check() {
+    let path = std::env::temp_dir().join("subnetting_saved_plan_passes_check.csv");
+    let path = path.to_str().unwrap();
+    assert!(
+        run(&["-n", "10.0.0.0/24", "--hosts", "50,20", "-o", path], "")
+            .status
+            .success()
+    );
+
+    let output = run(&["--check", path, "--parent", "10.0.0.0/24"], "");
+    std::fs::remove_file(path).unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .ends_with("2 subnets checked, no discrepancies\n"));
+}
+
+#[test]
+fn corrupted_plan_fails_check() {
+    let path = std::env::temp_dir().join("subnetting_corrupted_plan_fails_check.csv");
+    let path = path.to_str().unwrap();
+    assert!(
+        run(&["-n", "10.0.0.0/24", "--hosts", "50,20", "-o", path], "")
+            .status
+            .success()
+    );
+
+    // The broadcast of the /26 is changed from 10.0.0.63 to 10.0.0.64
+    let plan = std::fs::read_to_string(path).unwrap();
+    std::fs::write(path, plan.replacen("10.0.0.63", "10.0.0.64", 1)).unwrap();
+
+    let output = run(&["--check", path], "");
+    std::fs::remove_file(path).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(51));
+    assert!(stderr.contains("Subnet #1: broadcast is 10.0.0.64, expected 10.0.0.63"));
+    assert!(stderr.contains("Subnet #1: overlaps subnet #2"));
+    assert!(stderr.ends_with("Error: Invalid plan: 2 discrepancies found\n"));
+}
@@ -0,0 +1,34 @@
+//! Helpers shared by the integration tests of the binary
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs the binary with the given arguments and standard input
+#[allow(dead_code)]
+pub fn run(args: &[&str], input: &str) -> Output {
+    run_with_env(args, &[], input)
+}
+
+/**
+ * Runs the binary with the given arguments, environment variables and standard input <br>
+ * The defaults read from the environment of the tests (e.g. `SUBNET_FORMAT`) are removed first
+ */
+pub fn run_with_env(args: &[&str], envs: &[(&str, &str)], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_subnetting"))
+        .args(args)
+        .env_remove("SUBNET_FORMAT")
+        .env_remove("SUBNET_GATEWAY")
+        .envs(envs.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::run;
+
+const DUPLICATE_SALES: &str = "10.0.0.0/24\nsubnet_name,host_count\nSales,50\nDMZ,10\nSales,50\n";
+
+#[test]
+fn duplicate_named_subnet_is_a_warning() {
+    let output = run(&["--stdin", "-f", "prefixes"], DUPLICATE_SALES);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert!(stderr.contains(
+        "Warning: subnet #3 (Sales) repeats an earlier subnet, use --dedupe to collapse it"
+    ));
+    assert_eq!(stdout.lines().count(), 3);
+}
+
+#[test]
+fn dedupe_collapses_duplicate_named_subnet() {
+    let output = run(&["--stdin", "--dedupe", "-f", "prefixes"], DUPLICATE_SALES);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success());
+    assert!(stderr.contains("Collapsed 1 duplicate subnet(s)"));
+    assert!(!stderr.contains("Warning"));
+    assert_eq!(stdout, "10.0.0.0/26\n10.0.0.64/28\n");
+}
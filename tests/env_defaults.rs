@@ -0,0 +1,59 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::run_with_env;
+
+const ARGS: [&str; 4] = ["-n", "192.168.1.0/24", "--hosts", "50"];
+
+#[test]
+fn env_format_changes_the_default_format() {
+    let output = run_with_env(&ARGS, &[("SUBNET_FORMAT", "prefixes")], "");
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "192.168.1.0/26\n"
+    );
+}
+
+#[test]
+fn explicit_format_wins_over_env() {
+    let output = run_with_env(
+        &[&ARGS[..], &["--format", "summary"]].concat(),
+        &[("SUBNET_FORMAT", "prefixes")],
+        "",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "192.168.1.0/26  hosts 62  bcast 192.168.1.63  gw 192.168.1.62\n"
+    );
+}
+
+#[test]
+fn env_gateway_changes_the_default_gateway() {
+    let args = [&ARGS[..], &["--template", "{gateway}"]].concat();
+
+    let output = run_with_env(&args, &[("SUBNET_GATEWAY", "first")], "");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "192.168.1.1\n");
+
+    let output = run_with_env(
+        &[&args[..], &["--gateway", "last"]].concat(),
+        &[("SUBNET_GATEWAY", "first")],
+        "",
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "192.168.1.62\n");
+}
+
+#[test]
+fn invalid_env_value_fails_at_startup() {
+    let output = run_with_env(&ARGS, &[("SUBNET_FORMAT", "jsn")], "");
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("invalid value 'jsn'"));
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::run;
+
+/// Answers of the FLSM mode (option 3) up to the save prompt
+const SPLIT_INPUT: &str = "3\n10.0.0.0/24\n4\n";
+
+#[test]
+fn affirmative_answer_saves_the_results() {
+    let path = std::env::temp_dir().join("subnetting_affirmative_answer_saves.txt");
+    let output = run(
+        &["--quiet"],
+        &format!("{}YES\n{}\n", SPLIT_INPUT, path.display()),
+    );
+
+    assert!(output.status.success());
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(contents.starts_with("10.0.0.0/26\n"));
+}
+
+#[test]
+fn negative_answer_doesnt_ask_again() {
+    let output = run(&["--quiet"], &format!("{} No \n", SPLIT_INPUT));
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().is_empty());
+}
+
+#[test]
+fn ambiguous_answer_is_asked_again_once() {
+    let path = std::env::temp_dir().join("subnetting_ambiguous_answer_asked_again.txt");
+    let output = run(
+        &["--quiet"],
+        &format!("{}ya\ny\n{}\n", SPLIT_INPUT, path.display()),
+    );
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("'ya' isn't a valid answer"));
+    assert!(path.exists());
+    std::fs::remove_file(&path).unwrap();
+
+    let output = run(&["--quiet"], &format!("{}ya\nmaybe\n", SPLIT_INPUT));
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("'maybe' isn't a valid answer, the results aren't saved"));
+}
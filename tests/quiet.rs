@@ -0,0 +1,53 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::run;
+
+#[test]
+fn quiet_interactive_output_has_no_banner() {
+    let output = run(
+        &["--quiet", "--format", "prefixes"],
+        "3\n10.0.0.0/24\n4\nn\n",
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(!stdout.contains("Choose an option"));
+    assert!(!stdout.contains("Enter"));
+    assert_eq!(
+        stdout,
+        "10.0.0.0/26\n10.0.0.64/26\n10.0.0.128/26\n10.0.0.192/26\n"
+    );
+}
+
+#[test]
+fn quiet_without_output_still_prints_results() {
+    let output = run(
+        &[
+            "--quiet",
+            "-n",
+            "192.168.1.0/24",
+            "--hosts",
+            "50",
+            "-f",
+            "prefixes",
+        ],
+        "",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "192.168.1.0/26\n"
+    );
+}
+
+#[test]
+fn interactive_output_has_banner_without_quiet() {
+    let output = run(&["--format", "prefixes"], "3\n10.0.0.0/24\n4\nn\n");
+
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Choose an option"));
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::run;
+
+#[test]
+fn ipv6_network_is_split_without_prompts() {
+    let output = run(
+        &[
+            "--network",
+            "2001:db8::/48",
+            "--split",
+            "64",
+            "--count",
+            "4",
+        ],
+        "",
+    );
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("## Subnet Info:").count(), 4);
+    assert!(stdout.contains("\t - Network: 2001:db8:0:3::"));
+    assert!(stdout.contains("\t - Last Address: 2001:db8:0:3:ffff:ffff:ffff:ffff"));
+    assert!(!stdout.contains("Enter"));
+}
+
+#[test]
+fn ipv4_network_is_split_into_prefixes() {
+    let output = run(
+        &[
+            "--network",
+            "10.0.0.0/24",
+            "--split",
+            "26",
+            "--count",
+            "2",
+            "--format",
+            "prefixes",
+        ],
+        "",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "10.0.0.0/26\n10.0.0.64/26\n"
+    );
+}
+
+#[test]
+fn ipv6_split_rejects_the_ipv4_formats() {
+    let output = run(
+        &[
+            "--network",
+            "2001:db8::/48",
+            "--split",
+            "64",
+            "--count",
+            "2",
+            "--format",
+            "csv",
+        ],
+        "",
+    );
+
+    assert_eq!(output.status.code(), Some(43));
+}